@@ -0,0 +1,59 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Connection settings for a single named profile.
+#[derive(Debug, Default, Deserialize)]
+pub struct Profile {
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+}
+
+/// The on-disk CLI configuration file.
+///
+/// ```toml
+/// [profiles.default]
+/// api_key = "veil_live_xxxxx"
+///
+/// [profiles.staging]
+/// api_key = "veil_test_xxxxx"
+/// base_url = "https://staging-api.example.com"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl Config {
+    /// Load the config file, returning an empty config when it does not exist.
+    pub fn load(path: Option<PathBuf>) -> Result<Self, String> {
+        let Some(path) = path.or_else(default_path) else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| format!("Invalid config file {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Remove and return the named profile.
+    pub fn take_profile(&mut self, name: &str) -> Option<Profile> {
+        self.profiles.remove(name)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/veilmail/config.toml`, falling back to `~/.config`.
+fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(base.join("veilmail").join("config.toml"))
+}
@@ -0,0 +1,154 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Router;
+use serde_json::Value;
+use std::sync::Arc;
+
+use veilmail::webhook::{self, TestEventBuilder, WebhookEvent, SIGNATURE_HEADER};
+
+use crate::CliResult;
+
+struct Listener {
+    secret: Option<String>,
+    forward_to: Option<String>,
+    client: reqwest::Client,
+}
+
+/// Run a local webhook receiver until interrupted.
+///
+/// Every request is verified against `secret` (when given), printed, and
+/// relayed unchanged to `forward_to`.
+pub async fn listen(
+    port: u16,
+    secret: Option<String>,
+    forward_to: Option<String>,
+) -> CliResult<()> {
+    let state = Arc::new(Listener {
+        secret,
+        forward_to,
+        client: reqwest::Client::new(),
+    });
+    let app = Router::new().fallback(receive).with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    match &state.forward_to {
+        Some(url) => eprintln!("Forwarding events to {}", url),
+        None => eprintln!("No --forward-to given; events are only printed"),
+    }
+    if state.secret.is_none() {
+        eprintln!("No signing secret given; signatures are not verified");
+    }
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn receive(
+    State(state): State<Arc<Listener>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    // Verify and forward the raw bytes; any re-encoding would break the
+    // signature and change what the handler receives.
+    let verified = match &state.secret {
+        Some(secret) => {
+            let valid = webhook::verify_signature(&body, signature, secret);
+            if !valid {
+                eprintln!("✗ rejected request with invalid signature");
+                return StatusCode::UNAUTHORIZED;
+            }
+            "signature ok"
+        }
+        None => "signature not checked",
+    };
+
+    let text = String::from_utf8_lossy(&body);
+    match WebhookEvent::parse(&text) {
+        Ok(event) => print_event(&event, verified),
+        Err(e) => eprintln!("? unparseable event ({}): {}", e, text),
+    }
+
+    let Some(url) = &state.forward_to else {
+        return StatusCode::OK;
+    };
+
+    let content_type = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json");
+    let result = state
+        .client
+        .post(url)
+        .header("content-type", content_type)
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await;
+
+    match result {
+        Ok(resp) => {
+            let status = resp.status();
+            eprintln!("  → {} {}", url, status);
+            StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY)
+        }
+        Err(e) => {
+            eprintln!("  → {} failed: {}", url, e);
+            StatusCode::BAD_GATEWAY
+        }
+    }
+}
+
+fn print_event(event: &WebhookEvent, verified: &str) {
+    println!(
+        "● {} {} ({}){}",
+        event.event_type,
+        event.id,
+        verified,
+        event
+            .created_at
+            .as_deref()
+            .map(|t| format!(" at {}", t))
+            .unwrap_or_default()
+    );
+    if let Ok(data) = serde_json::to_string_pretty(&event.data) {
+        for line in data.lines() {
+            println!("  {}", line);
+        }
+    }
+}
+
+/// Sign and POST a synthetic event of the given type to `url`.
+pub async fn trigger(
+    event_type: &str,
+    url: &str,
+    secret: &str,
+    data: Option<Value>,
+) -> CliResult<()> {
+    let mut builder = TestEventBuilder::new(event_type);
+    if let Some(data) = data {
+        builder = builder.data(data);
+    }
+    let delivery = builder.sign(secret);
+
+    let mut req = reqwest::Client::new().post(url).body(delivery.body.clone());
+    for (name, value) in &delivery.headers {
+        req = req.header(name, value);
+    }
+    let status = req.send().await?.status();
+
+    println!(
+        "{} {} → {}",
+        delivery.event.event_type, delivery.event.id, status
+    );
+    if !status.is_success() {
+        return Err(format!("handler responded with {}", status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,681 @@
+//! `veilmail` — command-line access to the Veil Mail API.
+//!
+//! The API key is read from `--api-key`, the `VEILMAIL_API_KEY` environment
+//! variable, or the selected profile in `~/.config/veilmail/config.toml`, in
+//! that order.
+
+mod config;
+mod listen;
+mod output;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use config::Config;
+use output::Format;
+use veilmail::dns::export::{self, ExportOptions};
+use veilmail::polling::PollPolicy;
+use veilmail::resources::audiences::SubscriberFilter;
+use veilmail::schedule::SendAt;
+use veilmail::subscribers::export::{ExportFormat, SubscriberExport};
+use veilmail::subscribers::import::CsvImport;
+use veilmail::sync::sequences;
+use veilmail::sync::templates::{self, SyncOptions};
+use veilmail::{VeilMail, VeilMailOptions};
+
+type CliResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "veilmail",
+    version,
+    about = "Command-line tool for the Veil Mail API"
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct GlobalArgs {
+    /// API key (overrides the profile).
+    #[arg(long, env = "VEILMAIL_API_KEY", hide_env_values = true, global = true)]
+    api_key: Option<String>,
+
+    /// Profile to load from the config file.
+    #[arg(
+        long,
+        env = "VEILMAIL_PROFILE",
+        default_value = "default",
+        global = true
+    )]
+    profile: String,
+
+    /// Path to the config file.
+    #[arg(long, env = "VEILMAIL_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// Custom API base URL.
+    #[arg(long, env = "VEILMAIL_BASE_URL", global = true)]
+    base_url: Option<String>,
+
+    /// Output format.
+    #[arg(short, long, value_enum, default_value = "json", global = true)]
+    output: Format,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Send a single email.
+    Send(SendArgs),
+    /// Inspect and manage sent emails.
+    #[command(subcommand)]
+    Emails(EmailsCommand),
+    /// Manage sending domains.
+    #[command(subcommand)]
+    Domains(DomainsCommand),
+    /// Manage email templates.
+    #[command(subcommand)]
+    Templates(TemplatesCommand),
+    /// Manage audiences.
+    #[command(subcommand)]
+    Audiences(AudiencesCommand),
+    /// Control campaign delivery.
+    #[command(subcommand)]
+    Campaigns(CampaignsCommand),
+    /// Manage automation sequences.
+    #[command(subcommand)]
+    Sequences(SequencesCommand),
+    /// Manage webhook endpoints.
+    #[command(subcommand)]
+    Webhooks(WebhooksCommand),
+    /// Receive webhooks locally, verify and print them, and forward them.
+    Listen(ListenArgs),
+    /// Sign and post a synthetic webhook event to a local handler.
+    Trigger(TriggerArgs),
+}
+
+#[derive(Args)]
+struct ListenArgs {
+    /// Local port to listen on.
+    #[arg(long, default_value_t = 4242)]
+    port: u16,
+    /// Webhook signing secret used to verify incoming requests.
+    #[arg(long, env = "VEILMAIL_WEBHOOK_SECRET", hide_env_values = true)]
+    secret: Option<String>,
+    /// Relay each request to this URL, e.g. `http://localhost:3000/webhooks`.
+    #[arg(long)]
+    forward_to: Option<String>,
+}
+
+#[derive(Args)]
+struct TriggerArgs {
+    /// Event type, e.g. `email.bounced`.
+    event_type: String,
+    /// URL of the webhook handler.
+    #[arg(long)]
+    url: String,
+    /// Webhook signing secret used to sign the request.
+    #[arg(long, env = "VEILMAIL_WEBHOOK_SECRET", hide_env_values = true)]
+    secret: String,
+    /// Event payload as a JSON object (defaults to sample data).
+    #[arg(long, value_parser = parse_json)]
+    data: Option<Value>,
+}
+
+#[derive(Args)]
+struct SendArgs {
+    #[arg(long)]
+    from: String,
+    /// Recipient address (repeatable).
+    #[arg(long, required = true)]
+    to: Vec<String>,
+    #[arg(long)]
+    subject: Option<String>,
+    #[arg(long, conflicts_with = "html_file")]
+    html: Option<String>,
+    /// Read the HTML body from a file.
+    #[arg(long)]
+    html_file: Option<PathBuf>,
+    #[arg(long)]
+    text: Option<String>,
+    /// Send using a stored template.
+    #[arg(long)]
+    template_id: Option<String>,
+    /// Template variables as a JSON object.
+    #[arg(long, value_parser = parse_json)]
+    data: Option<Value>,
+    /// Tag to attach (repeatable).
+    #[arg(long)]
+    tag: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum EmailsCommand {
+    /// List emails.
+    List(ListArgs),
+    /// Get a single email.
+    Get { id: String },
+    /// Cancel a scheduled email.
+    Cancel { id: String },
+}
+
+#[derive(Subcommand)]
+enum DomainsCommand {
+    /// Add a sending domain.
+    Create { domain: String },
+    /// Trigger DNS verification for a domain.
+    Verify {
+        id: String,
+        /// Keep checking until the domain is verified.
+        #[arg(long)]
+        wait: bool,
+        /// Give up waiting after this many seconds.
+        #[arg(long, default_value_t = 600, requires = "wait")]
+        timeout: u64,
+    },
+    /// List domains.
+    List(ListArgs),
+    /// Print a domain's required DNS records for DNS-as-code tools.
+    Export {
+        id: String,
+        #[arg(long, value_enum, default_value = "bind")]
+        format: DnsFormat,
+        /// Record TTL in seconds.
+        #[arg(long, default_value_t = 3600)]
+        ttl: u32,
+        /// HCL expression for the zone ID in Terraform output.
+        #[arg(long, default_value = "var.zone_id")]
+        zone_id: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DnsFormat {
+    Bind,
+    Route53,
+    Cloudflare,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum TemplatesCommand {
+    /// Create a template, or update it when `--id` is given.
+    Push(PushArgs),
+    /// Render a stored template with sample data.
+    Preview {
+        id: String,
+        /// Template variables as a JSON object.
+        #[arg(long, value_parser = parse_json)]
+        data: Option<Value>,
+    },
+    /// Create, update, and optionally delete templates to match a directory
+    /// of .html/.txt/.json files.
+    Sync {
+        dir: PathBuf,
+        /// Print the plan without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete remote templates with no local file.
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+#[derive(Args)]
+struct PushArgs {
+    /// Update this template instead of creating a new one.
+    #[arg(long)]
+    id: Option<String>,
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    subject: Option<String>,
+    #[arg(long)]
+    html_file: PathBuf,
+    #[arg(long)]
+    text_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum AudiencesCommand {
+    /// Manage subscribers of an audience.
+    #[command(subcommand)]
+    Subscribers(SubscribersCommand),
+}
+
+#[derive(Subcommand)]
+enum SubscribersCommand {
+    /// List subscribers of an audience.
+    List {
+        audience_id: String,
+        #[arg(long, value_parser = ["pending", "active", "unsubscribed", "bounced", "complained"])]
+        status: Option<String>,
+        /// Match email addresses and names.
+        #[arg(long)]
+        search: Option<String>,
+        #[arg(long)]
+        limit: Option<u32>,
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Import subscribers from a CSV file with a header row, or a JSON array
+    /// file.
+    Import {
+        audience_id: String,
+        file: PathBuf,
+        /// CSV column holding the email address.
+        #[arg(long, default_value = "email")]
+        email_column: String,
+        /// Store unmapped CSV columns as custom properties.
+        #[arg(long)]
+        extra_columns_as_properties: bool,
+    },
+    /// Export subscribers as CSV, NDJSON, or JSON.
+    Export {
+        audience_id: String,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: SubscriberFormat,
+        /// Comma-separated fields to include, e.g. `email,status,properties.plan`.
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// Write to a file instead of stdout.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SubscriberFormat {
+    Csv,
+    Ndjson,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum CampaignsCommand {
+    /// Schedule a campaign for later delivery.
+    Schedule {
+        id: String,
+        /// RFC 3339 timestamp with an offset, e.g. `2026-01-01T09:00:00Z`.
+        #[arg(long)]
+        at: SendAt,
+    },
+    /// Send a campaign immediately.
+    Send { id: String },
+    /// Pause a sending campaign.
+    Pause { id: String },
+}
+
+#[derive(Subcommand)]
+enum SequencesCommand {
+    /// Add, update, delete, and reorder steps to match a YAML or TOML
+    /// definition.
+    Sync {
+        file: PathBuf,
+        /// Sequence to converge (overrides `id` in the file).
+        #[arg(long)]
+        id: Option<String>,
+        /// Print the plan without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebhooksCommand {
+    /// Send a test event to a webhook endpoint.
+    Test { id: String },
+    /// Rotate the signing secret of a webhook endpoint.
+    RotateSecret { id: String },
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[arg(long)]
+    limit: Option<u32>,
+    #[arg(long)]
+    cursor: Option<String>,
+    #[arg(long)]
+    status: Option<String>,
+}
+
+impl ListArgs {
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(cursor) = &self.cursor {
+            query.push(("cursor", cursor.clone()));
+        }
+        if let Some(status) = &self.status {
+            query.push(("status", status.clone()));
+        }
+        query
+    }
+}
+
+fn parse_json(s: &str) -> Result<Value, String> {
+    serde_json::from_str(s).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+fn read_file(path: &PathBuf) -> CliResult<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e).into())
+}
+
+fn client(global: &GlobalArgs) -> CliResult<VeilMail> {
+    let mut config = Config::load(global.config.clone())?;
+    let profile = config.take_profile(&global.profile).unwrap_or_default();
+
+    let api_key =
+        global.api_key.clone().or(profile.api_key).ok_or(
+            "No API key: pass --api-key, set VEILMAIL_API_KEY, or add it to a config profile",
+        )?;
+    let base_url = global.base_url.clone().or(profile.base_url);
+
+    Ok(VeilMail::with_options(
+        &api_key,
+        Some(VeilMailOptions {
+            base_url: base_url.as_deref(),
+            timeout_secs: None,
+        }),
+    )?)
+}
+
+async fn run(cli: Cli) -> CliResult<()> {
+    // Local development commands don't talk to the API.
+    let command = match cli.command {
+        Command::Listen(args) => {
+            return listen::listen(args.port, args.secret, args.forward_to).await
+        }
+        Command::Trigger(args) => {
+            return listen::trigger(&args.event_type, &args.url, &args.secret, args.data).await
+        }
+        command => command,
+    };
+
+    let client = client(&cli.global)?;
+    let format = cli.global.output;
+
+    let result = match command {
+        Command::Send(args) => {
+            let mut body = Map::new();
+            body.insert("from".into(), json!(args.from));
+            body.insert("to".into(), json!(args.to));
+            if let Some(subject) = args.subject {
+                body.insert("subject".into(), json!(subject));
+            }
+            let html = match args.html_file {
+                Some(path) => Some(read_file(&path)?),
+                None => args.html,
+            };
+            if let Some(html) = html {
+                body.insert("html".into(), json!(html));
+            }
+            if let Some(text) = args.text {
+                body.insert("text".into(), json!(text));
+            }
+            if let Some(template_id) = args.template_id {
+                body.insert("templateId".into(), json!(template_id));
+            }
+            if let Some(data) = args.data {
+                body.insert("templateData".into(), data);
+            }
+            if !args.tag.is_empty() {
+                body.insert("tags".into(), json!(args.tag));
+            }
+            client.emails().send(Value::Object(body)).await?
+        }
+        Command::Emails(cmd) => match cmd {
+            EmailsCommand::List(args) => {
+                let query = args.query();
+                let params: Vec<(&str, &str)> =
+                    query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+                client.emails().list(Some(&params)).await?
+            }
+            EmailsCommand::Get { id } => client.emails().get(&id).await?,
+            EmailsCommand::Cancel { id } => client.emails().cancel(&id).await?,
+        },
+        Command::Domains(cmd) => match cmd {
+            DomainsCommand::Create { domain } => {
+                serde_json::to_value(client.domains().create(json!({ "domain": domain })).await?)?
+            }
+            DomainsCommand::Verify {
+                id, wait: false, ..
+            } => serde_json::to_value(client.domains().verify(&id).await?)?,
+            DomainsCommand::Verify { id, timeout, .. } => {
+                let policy = PollPolicy::new(Duration::from_secs(timeout));
+                let domain = client
+                    .domains()
+                    .wait_until_verified_with_progress(&id, policy, |change| {
+                        eprintln!(
+                            "{} {}: {}",
+                            change.record.kind, change.record.name, change.record.status
+                        )
+                    })
+                    .await?;
+                serde_json::to_value(domain)?
+            }
+            DomainsCommand::List(args) => {
+                let query = args.query();
+                let params: Vec<(&str, &str)> =
+                    query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+                client.domains().list(Some(&params)).await?
+            }
+            DomainsCommand::Export {
+                id,
+                format,
+                ttl,
+                zone_id,
+            } => {
+                let domain = client.domains().get(&id).await?;
+                let options = ExportOptions::default().ttl(ttl).zone_id(zone_id);
+                match format {
+                    DnsFormat::Bind => print!("{}", export::bind(&domain, &options)),
+                    DnsFormat::Route53 => print!("{}", export::route53(&domain, &options)),
+                    DnsFormat::Cloudflare => print!("{}", export::cloudflare(&domain, &options)),
+                    DnsFormat::Json => {
+                        output::print(&export::manifest(&domain, &options), Format::Json)
+                    }
+                }
+                return Ok(());
+            }
+        },
+        Command::Templates(cmd) => match cmd {
+            TemplatesCommand::Push(args) => {
+                let mut body = Map::new();
+                body.insert("name".into(), json!(args.name));
+                body.insert("html".into(), json!(read_file(&args.html_file)?));
+                if let Some(subject) = args.subject {
+                    body.insert("subject".into(), json!(subject));
+                }
+                if let Some(path) = args.text_file {
+                    body.insert("text".into(), json!(read_file(&path)?));
+                }
+                let template = match args.id {
+                    Some(id) => client.templates().update(&id, Value::Object(body)).await?,
+                    None => client.templates().create(Value::Object(body)).await?,
+                };
+                serde_json::to_value(template)?
+            }
+            TemplatesCommand::Preview { id, data } => {
+                client
+                    .templates()
+                    .preview(json!({
+                        "templateId": id,
+                        "templateData": data.unwrap_or_else(|| json!({})),
+                    }))
+                    .await?
+            }
+            TemplatesCommand::Sync {
+                dir,
+                dry_run,
+                prune,
+            } => {
+                let local = templates::load_dir(&dir)?;
+                let options = SyncOptions::default().prune(prune);
+                let plan = templates::plan(&client.templates(), local, options).await?;
+                if dry_run {
+                    print!("{}", plan);
+                    return Ok(());
+                }
+                eprint!("{}", plan);
+                serde_json::to_value(templates::apply(&client.templates(), &plan).await?)?
+            }
+        },
+        Command::Audiences(AudiencesCommand::Subscribers(cmd)) => match cmd {
+            SubscribersCommand::List {
+                audience_id,
+                status,
+                search,
+                limit,
+                cursor,
+            } => {
+                let mut filter = SubscriberFilter::new();
+                if let Some(status) = status {
+                    filter = filter.status(serde_json::from_value(json!(status))?);
+                }
+                if let Some(search) = search {
+                    filter = filter.search(search);
+                }
+                if let Some(limit) = limit {
+                    filter = filter.limit(limit);
+                }
+                if let Some(cursor) = cursor {
+                    filter = filter.cursor(cursor);
+                }
+                let page = client
+                    .audiences()
+                    .subscribers(&audience_id)
+                    .list(&filter)
+                    .await?;
+                serde_json::to_value(page)?
+            }
+            SubscribersCommand::Import {
+                audience_id,
+                file,
+                email_column,
+                extra_columns_as_properties,
+            } if file
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) =>
+            {
+                let reader = tokio::fs::File::open(&file)
+                    .await
+                    .map_err(|e| format!("{}: {}", file.display(), e))?;
+                let options = CsvImport::new()
+                    .email_column(email_column)
+                    .extra_columns_as_properties(extra_columns_as_properties);
+                let report = client
+                    .audiences()
+                    .subscribers(&audience_id)
+                    .import_csv(reader, &options)
+                    .await?;
+                if let Some(stop) = &report.stopped {
+                    output::print(&serde_json::to_value(&report)?, format);
+                    return Err(format!("import incomplete: {}", stop).into());
+                }
+                serde_json::to_value(report)?
+            }
+            SubscribersCommand::Import {
+                audience_id, file, ..
+            } => {
+                let subscribers: Value = serde_json::from_str(&read_file(&file)?)?;
+                if !subscribers.is_array() {
+                    return Err(format!("{} must contain a JSON array", file.display()).into());
+                }
+                client
+                    .audiences()
+                    .subscribers(&audience_id)
+                    .import(json!({ "subscribers": subscribers }))
+                    .await?
+            }
+            SubscribersCommand::Export {
+                audience_id,
+                format,
+                columns,
+                out,
+            } => {
+                let format = match format {
+                    SubscriberFormat::Csv => ExportFormat::Csv,
+                    SubscriberFormat::Ndjson => ExportFormat::Ndjson,
+                    SubscriberFormat::Json => ExportFormat::Json,
+                };
+                let mut options = SubscriberExport::new(format);
+                if !columns.is_empty() {
+                    options = options.columns(columns);
+                }
+                let subscribers = client.audiences().subscribers(&audience_id);
+                let count = match out {
+                    Some(path) => {
+                        let mut file = tokio::fs::File::create(&path)
+                            .await
+                            .map_err(|e| format!("{}: {}", path.display(), e))?;
+                        subscribers.export_to(&mut file, &options).await?
+                    }
+                    None => {
+                        subscribers
+                            .export_to(&mut tokio::io::stdout(), &options)
+                            .await?
+                    }
+                };
+                eprintln!("Exported {} subscriber(s)", count);
+                return Ok(());
+            }
+        },
+        Command::Campaigns(cmd) => match cmd {
+            CampaignsCommand::Schedule { id, at } => {
+                serde_json::to_value(client.campaigns().schedule_at(&id, at).await?)?
+            }
+            CampaignsCommand::Send { id } => {
+                serde_json::to_value(client.campaigns().send(&id).await?)?
+            }
+            CampaignsCommand::Pause { id } => {
+                serde_json::to_value(client.campaigns().pause(&id).await?)?
+            }
+        },
+        Command::Sequences(SequencesCommand::Sync { file, id, dry_run }) => {
+            let mut definition = sequences::load_file(&file)?;
+            if id.is_some() {
+                definition.id = id;
+            }
+            let plan =
+                sequences::plan(&client.sequences(), &client.templates(), &definition).await?;
+            if dry_run {
+                print!("{}", plan);
+                return Ok(());
+            }
+            eprint!("{}", plan);
+            serde_json::to_value(sequences::apply(&client.sequences(), &plan).await?)?
+        }
+        Command::Webhooks(cmd) => match cmd {
+            WebhooksCommand::Test { id } => client.webhooks().test(&id).await?,
+            WebhooksCommand::RotateSecret { id } => client.webhooks().rotate_secret(&id).await?,
+        },
+        Command::Listen(_) | Command::Trigger(_) => unreachable!("handled above"),
+    };
+
+    output::print(&result, format);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+const MAX_CELL_WIDTH: usize = 48;
+
+/// How command results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Table,
+}
+
+pub fn print(value: &Value, format: Format) {
+    match format {
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+        ),
+        Format::Table => print!("{}", table(value)),
+    }
+}
+
+/// Render a response as a plain-text table.
+///
+/// List responses (`{ "data": [...] }` or a bare array) become one row per
+/// item; single objects become a two-column key/value table.
+fn table(value: &Value) -> String {
+    let rows = match value {
+        Value::Array(items) => Some(items),
+        Value::Object(map) => map.get("data").and_then(Value::as_array),
+        _ => None,
+    };
+
+    match (rows, value) {
+        (Some(items), _) => list_table(items),
+        (None, Value::Object(map)) => {
+            let rows: Vec<Vec<String>> =
+                map.iter().map(|(k, v)| vec![k.clone(), cell(v)]).collect();
+            render(&["FIELD".to_string(), "VALUE".to_string()], &rows)
+        }
+        (None, other) => format!("{}\n", cell(other)),
+    }
+}
+
+fn list_table(items: &[Value]) -> String {
+    if items.is_empty() {
+        return "(no results)\n".to_string();
+    }
+
+    let mut columns: Vec<String> = Vec::new();
+    for item in items {
+        if let Value::Object(map) = item {
+            for (key, v) in map {
+                if !v.is_object() && !v.is_array() && !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+
+    if columns.is_empty() {
+        let rows: Vec<Vec<String>> = items.iter().map(|v| vec![cell(v)]).collect();
+        return render(&["VALUE".to_string()], &rows);
+    }
+
+    let rows: Vec<Vec<String>> = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|c| item.get(c).map(cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    let headers: Vec<String> = columns.iter().map(|c| c.to_uppercase()).collect();
+    render(&headers, &rows)
+}
+
+fn cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    if text.chars().count() > MAX_CELL_WIDTH {
+        let truncated: String = text.chars().take(MAX_CELL_WIDTH - 1).collect();
+        format!("{}…", truncated)
+    } else {
+        text
+    }
+}
+
+fn render(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, c) in row.iter().enumerate() {
+            widths[i] = widths[i].max(c.chars().count());
+        }
+    }
+
+    let line = |cells: &[String]| -> String {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<width$}", c, width = *w))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut out = line(headers);
+    for row in rows {
+        out.push_str(&line(row));
+    }
+    out
+}
@@ -0,0 +1,11 @@
+//! Helpers shared by the CSV writers.
+
+/// Quote a field if it contains a delimiter, quote, or line break
+/// (RFC 4180).
+pub(crate) fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
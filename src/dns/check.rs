@@ -0,0 +1,447 @@
+//! Diagnose a domain's DNS setup locally before calling `Domains::verify`.
+//!
+//! Requires the `dns-check` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use veilmail::dns::check::DnsChecker;
+//!
+//! let domain = client.domains().get("domain_xxxxx").await?;
+//! let report = DnsChecker::system()?.check(&domain).await;
+//!
+//! for record in &report.records {
+//!     println!("{} {}: {:?}", record.record.kind, record.record.name, record.status);
+//!     for problem in &record.problems {
+//!         println!("  - {}", problem);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use super::policy::{DmarcPolicy, DmarcRecord, SpfRecord};
+use crate::error::{Result, VeilMailError};
+use crate::resources::domains::{DnsRecord, DnsRecordKind, Domain, RecordPurpose};
+
+/// Maximum DNS lookups an SPF record may cause (RFC 7208 §4.6.4).
+const SPF_LOOKUP_LIMIT: usize = 10;
+
+/// Outcome of checking a single required record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The record is published as expected.
+    Ok,
+    /// Nothing was found at the record name.
+    Missing,
+    /// Something was found, but it does not match the expected value.
+    Mismatch,
+    /// The lookup itself failed (timeout, SERVFAIL, ...).
+    LookupFailed,
+    /// The record type is unknown to this SDK, so it was not checked.
+    Skipped,
+}
+
+/// A specific mistake detected while checking a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// More than one SPF record is published; receivers treat this as a
+    /// permanent error.
+    DuplicateSpf { count: usize },
+    /// The published SPF record does not include Veil Mail.
+    SpfMissingInclude { include: String },
+    /// The SPF record needs more than 10 DNS lookups.
+    SpfTooManyLookups { count: usize },
+    /// More than one DMARC record is published.
+    DuplicateDmarc { count: usize },
+    /// The DMARC record has no valid `p=` tag.
+    DmarcMissingPolicy,
+    /// `p=none` only monitors; it does not protect the domain.
+    DmarcMonitorOnly,
+    /// A CNAME was expected but the name resolves directly to other records,
+    /// usually because the DNS provider flattens or proxies CNAMEs.
+    CnameFlattened,
+    /// The record was found under the name with the zone appended again,
+    /// e.g. `mail.example.com.example.com`, typically from entering a full
+    /// name in a DNS panel that adds the zone itself.
+    ZoneAppendedToName { found_at: String },
+    /// The CNAME or MX target has the zone appended, typically from a
+    /// zone file entry missing its trailing dot.
+    ZoneAppendedToTarget { found: String },
+    /// The lookup failed.
+    Lookup(String),
+    /// The API returned a record type this SDK cannot look up.
+    UnsupportedType,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::DuplicateSpf { count } => {
+                write!(f, "{} SPF records published; merge them into one", count)
+            }
+            Problem::SpfMissingInclude { include } => {
+                write!(f, "SPF record is missing include:{}", include)
+            }
+            Problem::SpfTooManyLookups { count } => write!(
+                f,
+                "SPF record needs {} DNS lookups (limit is {})",
+                count, SPF_LOOKUP_LIMIT
+            ),
+            Problem::DuplicateDmarc { count } => {
+                write!(f, "{} DMARC records published; keep only one", count)
+            }
+            Problem::DmarcMissingPolicy => f.write_str("DMARC record has no valid p= policy"),
+            Problem::DmarcMonitorOnly => f.write_str("DMARC policy is p=none, which only monitors"),
+            Problem::CnameFlattened => f.write_str(
+                "expected a CNAME but found other records; disable CNAME flattening or proxying",
+            ),
+            Problem::ZoneAppendedToName { found_at } => write!(
+                f,
+                "record found at {}; the zone name was appended twice",
+                found_at
+            ),
+            Problem::ZoneAppendedToTarget { found } => {
+                write!(f, "target is {}; add a trailing dot to the value", found)
+            }
+            Problem::Lookup(message) => write!(f, "lookup failed: {}", message),
+            Problem::UnsupportedType => {
+                f.write_str("record type is not supported by this SDK; check it manually")
+            }
+        }
+    }
+}
+
+/// Diagnosis of one required record.
+#[derive(Debug, Clone)]
+pub struct RecordDiagnosis {
+    /// The record Veil Mail expects.
+    pub record: DnsRecord,
+    pub purpose: RecordPurpose,
+    pub status: CheckStatus,
+    /// Values actually published at the record name.
+    pub found: Vec<String>,
+    pub problems: Vec<Problem>,
+}
+
+/// Diagnosis of every record a domain requires.
+#[derive(Debug, Clone)]
+pub struct DomainDiagnosis {
+    pub domain: String,
+    pub records: Vec<RecordDiagnosis>,
+}
+
+impl DomainDiagnosis {
+    /// Whether every record is published as expected. Records of unknown
+    /// type ([`CheckStatus::Skipped`]) are not counted.
+    pub fn is_ok(&self) -> bool {
+        self.records
+            .iter()
+            .all(|r| matches!(r.status, CheckStatus::Ok | CheckStatus::Skipped))
+    }
+}
+
+/// Looks up a domain's required records and explains what is wrong.
+pub struct DnsChecker {
+    resolver: TokioAsyncResolver,
+    config: ResolverConfig,
+    opts: ResolverOpts,
+}
+
+impl DnsChecker {
+    /// Use the system's configured nameservers.
+    pub fn system() -> Result<Self> {
+        let (config, opts) = hickory_resolver::system_conf::read_system_conf()
+            .map_err(|e| VeilMailError::Other(format!("Could not read DNS config: {}", e)))?;
+        Ok(Self::from_config(config, opts))
+    }
+
+    /// Query a specific nameserver over UDP and TCP, e.g. a local test server
+    /// or the domain's authoritative server to bypass caches.
+    pub fn with_nameserver(addr: SocketAddr) -> Self {
+        let servers = NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true);
+        Self::from_config(
+            ResolverConfig::from_parts(None, vec![], servers),
+            ResolverOpts::default(),
+        )
+    }
+
+    /// Set the per-query timeout.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        let mut opts = self.opts;
+        opts.timeout = timeout;
+        Self::from_config(self.config, opts)
+    }
+
+    fn from_config(config: ResolverConfig, mut opts: ResolverOpts) -> Self {
+        // Always see the current state of the zone.
+        opts.cache_size = 0;
+        opts.use_hosts_file = false;
+        Self {
+            resolver: TokioAsyncResolver::tokio(config.clone(), opts.clone()),
+            config,
+            opts,
+        }
+    }
+
+    /// Check every record the domain requires.
+    pub async fn check(&self, domain: &Domain) -> DomainDiagnosis {
+        let mut records = Vec::with_capacity(domain.records.len());
+        for record in &domain.records {
+            records.push(self.check_record(&domain.name, record).await);
+        }
+
+        DomainDiagnosis {
+            domain: domain.name.clone(),
+            records,
+        }
+    }
+
+    /// Check a single record of the given domain.
+    pub async fn check_record(&self, domain: &str, record: &DnsRecord) -> RecordDiagnosis {
+        let purpose = record.purpose();
+        let mut diagnosis = RecordDiagnosis {
+            record: record.clone(),
+            purpose,
+            status: CheckStatus::Ok,
+            found: Vec::new(),
+            problems: Vec::new(),
+        };
+
+        if record.kind == DnsRecordKind::Unknown {
+            diagnosis.status = CheckStatus::Skipped;
+            diagnosis.problems.push(Problem::UnsupportedType);
+            return diagnosis;
+        }
+
+        let found = match self.lookup(&record.name, record.kind).await {
+            Ok(found) => found,
+            Err(message) => {
+                diagnosis.status = CheckStatus::LookupFailed;
+                diagnosis.problems.push(Problem::Lookup(message));
+                return diagnosis;
+            }
+        };
+
+        let relevant: Vec<String> = match purpose {
+            RecordPurpose::Spf => found
+                .iter()
+                .filter(|v| SpfRecord::parse(v).is_some())
+                .cloned()
+                .collect(),
+            RecordPurpose::Dmarc => found
+                .iter()
+                .filter(|v| DmarcRecord::parse(v).is_some())
+                .cloned()
+                .collect(),
+            _ => found.clone(),
+        };
+        diagnosis.found = found;
+
+        if relevant.is_empty() {
+            diagnosis.status = CheckStatus::Missing;
+            self.diagnose_missing(domain, record, &mut diagnosis).await;
+            return diagnosis;
+        }
+
+        match purpose {
+            RecordPurpose::Spf => check_spf(record, &relevant, &mut diagnosis),
+            RecordPurpose::Dmarc => check_dmarc(&relevant, &mut diagnosis),
+            _ => {
+                if !relevant.iter().any(|v| matches(record, v)) {
+                    diagnosis.status = CheckStatus::Mismatch;
+                    if let Some(found) = relevant
+                        .iter()
+                        .find(|v| has_zone_appended(&record.value, v, domain))
+                    {
+                        diagnosis.problems.push(Problem::ZoneAppendedToTarget {
+                            found: found.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        diagnosis
+    }
+
+    /// Look for the usual reasons a record cannot be found.
+    async fn diagnose_missing(
+        &self,
+        domain: &str,
+        record: &DnsRecord,
+        diagnosis: &mut RecordDiagnosis,
+    ) {
+        let name = record.name.trim_end_matches('.');
+
+        if record.kind == DnsRecordKind::Cname {
+            if let Ok(other) = self.lookup(name, DnsRecordKind::Txt).await {
+                if !other.is_empty() {
+                    diagnosis.status = CheckStatus::Mismatch;
+                    diagnosis.found = other;
+                    diagnosis.problems.push(Problem::CnameFlattened);
+                    return;
+                }
+            }
+        }
+
+        for zone in zone_candidates(domain) {
+            let doubled = format!("{}.{}", name, zone);
+            if let Ok(found) = self.lookup(&doubled, record.kind).await {
+                if found.iter().any(|v| matches(record, v)) {
+                    diagnosis
+                        .problems
+                        .push(Problem::ZoneAppendedToName { found_at: doubled });
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Return the values published at `name`, normalised for comparison.
+    async fn lookup(
+        &self,
+        name: &str,
+        kind: DnsRecordKind,
+    ) -> std::result::Result<Vec<String>, String> {
+        let record_type = match kind {
+            DnsRecordKind::Txt => RecordType::TXT,
+            DnsRecordKind::Cname => RecordType::CNAME,
+            DnsRecordKind::Mx => RecordType::MX,
+            DnsRecordKind::Unknown => return Err("unsupported record type".to_string()),
+        };
+        // A trailing dot stops the resolver from trying search domains.
+        let fqdn = format!("{}.", name.trim_end_matches('.'));
+
+        let lookup = match self.resolver.lookup(fqdn, record_type).await {
+            Ok(lookup) => lookup,
+            Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+
+        Ok(lookup
+            .record_iter()
+            .filter(|r| r.record_type() == record_type)
+            .filter_map(|r| match r.data()? {
+                RData::TXT(txt) => Some(
+                    txt.iter()
+                        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+                        .collect(),
+                ),
+                RData::CNAME(cname) => Some(normalize_host(&cname.0.to_string())),
+                RData::MX(mx) => Some(format!(
+                    "{} {}",
+                    mx.preference(),
+                    normalize_host(&mx.exchange().to_string())
+                )),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+fn check_spf(record: &DnsRecord, found: &[String], diagnosis: &mut RecordDiagnosis) {
+    if found.len() > 1 {
+        diagnosis.status = CheckStatus::Mismatch;
+        diagnosis
+            .problems
+            .push(Problem::DuplicateSpf { count: found.len() });
+    }
+
+    let expected = SpfRecord::parse(record.value.trim_matches('"'));
+    for published in found.iter().filter_map(|v| SpfRecord::parse(v)) {
+        for include in expected.iter().flat_map(|e| &e.includes) {
+            if !published.includes.contains(include) {
+                diagnosis.status = CheckStatus::Mismatch;
+                diagnosis.problems.push(Problem::SpfMissingInclude {
+                    include: include.clone(),
+                });
+            }
+        }
+
+        let count = published.lookup_count();
+        if count > SPF_LOOKUP_LIMIT {
+            diagnosis.status = CheckStatus::Mismatch;
+            diagnosis
+                .problems
+                .push(Problem::SpfTooManyLookups { count });
+        }
+    }
+}
+
+fn check_dmarc(found: &[String], diagnosis: &mut RecordDiagnosis) {
+    if found.len() > 1 {
+        diagnosis.status = CheckStatus::Mismatch;
+        diagnosis
+            .problems
+            .push(Problem::DuplicateDmarc { count: found.len() });
+    }
+
+    for published in found.iter().filter_map(|v| DmarcRecord::parse(v)) {
+        match published.policy {
+            None => {
+                diagnosis.status = CheckStatus::Mismatch;
+                diagnosis.problems.push(Problem::DmarcMissingPolicy);
+            }
+            // A warning only: Veil Mail accepts a monitoring policy.
+            Some(DmarcPolicy::None) => diagnosis.problems.push(Problem::DmarcMonitorOnly),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Whether a published value satisfies the expected record.
+fn matches(record: &DnsRecord, found: &str) -> bool {
+    match record.kind {
+        DnsRecordKind::Txt => {
+            let strip = |s: &str| -> String {
+                s.trim_matches('"')
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect()
+            };
+            strip(&record.value) == strip(found)
+        }
+        DnsRecordKind::Cname => normalize_host(&record.value) == found,
+        DnsRecordKind::Mx => {
+            let host = found.split_once(' ').map(|(_, h)| h).unwrap_or(found);
+            let priority_ok = record
+                .priority
+                .is_none_or(|p| found.starts_with(&format!("{} ", p)));
+            normalize_host(&record.value) == host && priority_ok
+        }
+        DnsRecordKind::Unknown => false,
+    }
+}
+
+fn has_zone_appended(expected: &str, found: &str, domain: &str) -> bool {
+    let expected = normalize_host(expected);
+    let host = found.rsplit(' ').next().unwrap_or(found);
+    zone_candidates(domain)
+        .iter()
+        .any(|zone| host == format!("{}.{}", expected, zone))
+}
+
+/// The domain and each parent with at least two labels, e.g.
+/// `mail.example.com` and `example.com`.
+fn zone_candidates(domain: &str) -> Vec<String> {
+    let labels: Vec<&str> = domain.trim_end_matches('.').split('.').collect();
+    (0..labels.len().saturating_sub(1))
+        .map(|i| labels[i..].join(".").to_ascii_lowercase())
+        .collect()
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
@@ -0,0 +1,267 @@
+//! Render a domain's required DNS records for infrastructure-as-code tools.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use veilmail::dns::export::{self, ExportOptions};
+//!
+//! let domain = client.domains().get("domain_xxxxx").await?;
+//! let options = ExportOptions::default().zone_id("aws_route53_zone.main.zone_id");
+//!
+//! std::fs::write("veilmail.tf", export::route53(&domain, &options)).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::resources::domains::{DnsRecord, DnsRecordKind, Domain};
+
+/// Maximum length of a single TXT character-string.
+const TXT_CHUNK_LEN: usize = 255;
+
+/// Settings shared by the exporters.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Record TTL in seconds.
+    pub ttl: u32,
+    /// HCL expression for the hosted zone ID in Terraform output.
+    pub zone_id: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            ttl: 3600,
+            zone_id: "var.zone_id".to_string(),
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn zone_id(mut self, zone_id: impl Into<String>) -> Self {
+        self.zone_id = zone_id.into();
+        self
+    }
+}
+
+/// Render the records as a BIND zone file snippet with absolute names.
+///
+/// Records of a type this SDK does not know are left out with a comment.
+pub fn bind(domain: &Domain, options: &ExportOptions) -> String {
+    let mut out = format!("; Veil Mail DNS records for {}\n", domain.name);
+
+    for record in &domain.records {
+        let data = match record.kind {
+            DnsRecordKind::Unknown => {
+                out.push_str(&format!(
+                    "; skipped {}: unsupported record type\n",
+                    absolute(&record.name)
+                ));
+                continue;
+            }
+            DnsRecordKind::Txt => txt_chunks(&record.value)
+                .iter()
+                .map(|chunk| format!("\"{}\"", escape_quoted(chunk)))
+                .collect::<Vec<_>>()
+                .join(" "),
+            DnsRecordKind::Cname => absolute(&record.value),
+            DnsRecordKind::Mx => {
+                format!(
+                    "{} {}",
+                    record.priority.unwrap_or(10),
+                    absolute(&record.value)
+                )
+            }
+        };
+        out.push_str(&format!(
+            "{}\t{}\tIN\t{}\t{}\n",
+            absolute(&record.name),
+            options.ttl,
+            record.kind,
+            data
+        ));
+    }
+
+    out
+}
+
+/// Render the records as Terraform `aws_route53_record` resources.
+///
+/// Records of a type this SDK does not know are left out with a comment.
+pub fn route53(domain: &Domain, options: &ExportOptions) -> String {
+    let mut out = skipped_comments(domain);
+
+    for (resource, record) in resource_names(&domain.records) {
+        let value = match record.kind {
+            // Route 53 splits long TXT values at embedded `""`.
+            DnsRecordKind::Txt => txt_chunks(&record.value)
+                .iter()
+                .map(|chunk| escape_hcl(chunk))
+                .collect::<Vec<_>>()
+                .join("\\\"\\\""),
+            DnsRecordKind::Cname => escape_hcl(record.value.trim_end_matches('.')),
+            DnsRecordKind::Mx => escape_hcl(&format!(
+                "{} {}",
+                record.priority.unwrap_or(10),
+                record.value.trim_end_matches('.')
+            )),
+            DnsRecordKind::Unknown => unreachable!("filtered by resource_names"),
+        };
+
+        out.push_str(&format!(
+            "resource \"aws_route53_record\" \"{}\" {{\n  zone_id = {}\n  name    = \"{}\"\n  type    = \"{}\"\n  ttl     = {}\n  records = [\"{}\"]\n}}\n\n",
+            resource,
+            options.zone_id,
+            escape_hcl(record.name.trim_end_matches('.')),
+            record.kind,
+            options.ttl,
+            value,
+        ));
+    }
+
+    out
+}
+
+/// Render the records as Terraform `cloudflare_record` resources.
+///
+/// Records of a type this SDK does not know are left out with a comment.
+pub fn cloudflare(domain: &Domain, options: &ExportOptions) -> String {
+    let mut out = skipped_comments(domain);
+
+    for (resource, record) in resource_names(&domain.records) {
+        let content = match record.kind {
+            DnsRecordKind::Txt => record.value.trim_matches('"'),
+            DnsRecordKind::Cname | DnsRecordKind::Mx => record.value.trim_end_matches('.'),
+            DnsRecordKind::Unknown => unreachable!("filtered by resource_names"),
+        };
+        let mut body = format!(
+            "  zone_id = {}\n  name    = \"{}\"\n  type    = \"{}\"\n  content = \"{}\"\n  ttl     = {}\n",
+            options.zone_id,
+            escape_hcl(record.name.trim_end_matches('.')),
+            record.kind,
+            escape_hcl(content),
+            options.ttl,
+        );
+        match record.kind {
+            DnsRecordKind::Mx => {
+                body.push_str(&format!("  priority = {}\n", record.priority.unwrap_or(10)));
+            }
+            // Proxied CNAMEs are flattened by Cloudflare and break DKIM and
+            // return-path lookups.
+            DnsRecordKind::Cname => body.push_str("  proxied = false\n"),
+            DnsRecordKind::Txt | DnsRecordKind::Unknown => {}
+        }
+
+        out.push_str(&format!(
+            "resource \"cloudflare_record\" \"{}\" {{\n{}}}\n\n",
+            resource, body
+        ));
+    }
+
+    out
+}
+
+/// Render the records as a tool-neutral JSON manifest.
+pub fn manifest(domain: &Domain, options: &ExportOptions) -> Value {
+    let records: Vec<Value> = domain
+        .records
+        .iter()
+        .map(|record| {
+            json!({
+                "purpose": record.purpose(),
+                "type": record.kind,
+                "name": record.name.trim_end_matches('.'),
+                "value": record.value,
+                "priority": record.priority,
+                "ttl": options.ttl,
+                "status": record.status,
+            })
+        })
+        .collect();
+
+    json!({
+        "domain": domain.name,
+        "status": domain.status,
+        "records": records,
+    })
+}
+
+/// Terraform comments for records of unknown type, which cannot be exported.
+fn skipped_comments(domain: &Domain) -> String {
+    domain
+        .records
+        .iter()
+        .filter(|r| r.kind == DnsRecordKind::Unknown)
+        .map(|r| {
+            format!(
+                "# skipped {}: unsupported record type\n\n",
+                r.name.trim_end_matches('.')
+            )
+        })
+        .collect()
+}
+
+/// Pair each record of a known type with a unique Terraform resource name
+/// such as `veilmail_dkim_2`.
+fn resource_names(records: &[DnsRecord]) -> Vec<(String, &DnsRecord)> {
+    let records: Vec<&DnsRecord> = records
+        .iter()
+        .filter(|r| r.kind != DnsRecordKind::Unknown)
+        .collect();
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for record in &records {
+        *totals.entry(record.purpose().as_str()).or_default() += 1;
+    }
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    records
+        .into_iter()
+        .map(|record| {
+            let purpose = record.purpose().as_str();
+            let n = seen.entry(purpose).or_default();
+            *n += 1;
+            let name = if totals[purpose] > 1 {
+                format!("veilmail_{}_{}", purpose, n)
+            } else {
+                format!("veilmail_{}", purpose)
+            };
+            (name, record)
+        })
+        .collect()
+}
+
+fn absolute(name: &str) -> String {
+    if name.ends_with('.') {
+        name.to_string()
+    } else {
+        format!("{}.", name)
+    }
+}
+
+fn txt_chunks(value: &str) -> Vec<String> {
+    let value = value.trim_matches('"');
+    let chars: Vec<char> = value.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(TXT_CHUNK_LEN)
+        .map(|c| c.iter().collect())
+        .collect()
+}
+
+fn escape_quoted(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_hcl(s: &str) -> String {
+    escape_quoted(s).replace("${", "$${").replace("%{", "%%{")
+}
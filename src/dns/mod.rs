@@ -0,0 +1,6 @@
+//! Tooling for the DNS records a sending domain requires.
+
+#[cfg(feature = "dns-check")]
+pub mod check;
+pub mod export;
+pub mod policy;
@@ -0,0 +1,184 @@
+//! Parsers for SPF and DMARC TXT records.
+
+use std::fmt;
+
+/// A parsed SPF record (`v=spf1 ...`).
+///
+/// # Example
+///
+/// ```
+/// use veilmail::dns::policy::SpfRecord;
+///
+/// let spf = SpfRecord::parse("v=spf1 include:veilmail.xyz include:_spf.google.com ~all").unwrap();
+/// assert_eq!(spf.includes, ["veilmail.xyz", "_spf.google.com"]);
+/// assert_eq!(spf.all, Some('~'));
+/// assert_eq!(spf.lookup_count(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfRecord {
+    /// Every term after `v=spf1`, as written.
+    pub terms: Vec<String>,
+    /// Domains referenced by `include:` mechanisms.
+    pub includes: Vec<String>,
+    /// Qualifier of the `all` mechanism (`+`, `-`, `~`, or `?`), if present.
+    pub all: Option<char>,
+    /// Target of a `redirect=` modifier.
+    pub redirect: Option<String>,
+}
+
+impl SpfRecord {
+    /// Parse a TXT value, returning `None` if it is not an SPF record.
+    pub fn parse(txt: &str) -> Option<Self> {
+        let mut parts = txt.split_whitespace();
+        if !parts.next()?.eq_ignore_ascii_case("v=spf1") {
+            return None;
+        }
+
+        let mut record = SpfRecord {
+            terms: Vec::new(),
+            includes: Vec::new(),
+            all: None,
+            redirect: None,
+        };
+
+        for term in parts {
+            record.terms.push(term.to_string());
+            let (qualifier, mechanism) = match term.chars().next() {
+                Some(q @ ('+' | '-' | '~' | '?')) => (q, &term[1..]),
+                _ => ('+', term),
+            };
+            let lower = mechanism.to_ascii_lowercase();
+
+            if lower == "all" {
+                record.all = Some(qualifier);
+            } else if let Some(domain) = lower.strip_prefix("include:") {
+                record
+                    .includes
+                    .push(domain.trim_end_matches('.').to_string());
+            } else if let Some(domain) = lower.strip_prefix("redirect=") {
+                record.redirect = Some(domain.trim_end_matches('.').to_string());
+            }
+        }
+
+        Some(record)
+    }
+
+    /// Number of terms that cost a DNS lookup during evaluation.
+    ///
+    /// Receivers stop evaluating after 10 (RFC 7208 §4.6.4).
+    pub fn lookup_count(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|t| {
+                t.trim_start_matches(['+', '-', '~', '?'])
+                    .to_ascii_lowercase()
+            })
+            .filter(|t| {
+                t.starts_with("include:")
+                    || t.starts_with("redirect=")
+                    || t.starts_with("exists:")
+                    || t == "a"
+                    || t.starts_with("a:")
+                    || t.starts_with("a/")
+                    || t == "mx"
+                    || t.starts_with("mx:")
+                    || t.starts_with("mx/")
+                    || t == "ptr"
+                    || t.starts_with("ptr:")
+            })
+            .count()
+    }
+}
+
+/// The `p=` or `sp=` policy of a DMARC record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmarcPolicy {
+    None,
+    Quarantine,
+    Reject,
+}
+
+impl DmarcPolicy {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "none" => Some(DmarcPolicy::None),
+            "quarantine" => Some(DmarcPolicy::Quarantine),
+            "reject" => Some(DmarcPolicy::Reject),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DmarcPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DmarcPolicy::None => "none",
+            DmarcPolicy::Quarantine => "quarantine",
+            DmarcPolicy::Reject => "reject",
+        })
+    }
+}
+
+/// A parsed DMARC record (`v=DMARC1; p=...`).
+///
+/// # Example
+///
+/// ```
+/// use veilmail::dns::policy::{DmarcPolicy, DmarcRecord};
+///
+/// let dmarc = DmarcRecord::parse("v=DMARC1; p=quarantine; pct=50; rua=mailto:dmarc@example.com").unwrap();
+/// assert_eq!(dmarc.policy, Some(DmarcPolicy::Quarantine));
+/// assert_eq!(dmarc.percentage, Some(50));
+/// assert_eq!(dmarc.rua, ["mailto:dmarc@example.com"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmarcRecord {
+    /// `p=`; `None` if missing or invalid, which makes the record unusable.
+    pub policy: Option<DmarcPolicy>,
+    /// `sp=`, the policy for subdomains.
+    pub subdomain_policy: Option<DmarcPolicy>,
+    /// `pct=`, the percentage of failing mail the policy applies to.
+    pub percentage: Option<u8>,
+    /// `rua=` aggregate report URIs.
+    pub rua: Vec<String>,
+    /// `ruf=` forensic report URIs.
+    pub ruf: Vec<String>,
+}
+
+impl DmarcRecord {
+    /// Parse a TXT value, returning `None` if it is not a DMARC record.
+    pub fn parse(txt: &str) -> Option<Self> {
+        let mut tags = txt.split(';').map(str::trim).filter(|t| !t.is_empty());
+        let version = tags.next()?.replace(' ', "");
+        if !version.eq_ignore_ascii_case("v=DMARC1") {
+            return None;
+        }
+
+        let mut record = DmarcRecord {
+            policy: None,
+            subdomain_policy: None,
+            percentage: None,
+            rua: Vec::new(),
+            ruf: Vec::new(),
+        };
+
+        for tag in tags {
+            let Some((key, value)) = tag.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            let uris = || value.split(',').map(|u| u.trim().to_string()).collect();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "p" => record.policy = DmarcPolicy::parse(value),
+                "sp" => record.subdomain_policy = DmarcPolicy::parse(value),
+                "pct" => record.percentage = value.parse().ok(),
+                "rua" => record.rua = uris(),
+                "ruf" => record.ruf = uris(),
+                _ => {}
+            }
+        }
+
+        Some(record)
+    }
+}
@@ -1,6 +1,10 @@
 use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::resources::campaigns::{CampaignAction, CampaignStatus};
+use crate::resources::domains::{DnsRecord, Domain};
+
 /// Error types returned by the Veil Mail API.
 #[derive(Debug, Error)]
 pub enum VeilMailError {
@@ -50,6 +54,88 @@ pub enum VeilMailError {
         code: Option<String>,
     },
 
+    #[error(
+        "Domain {domain_id} not verified after {}s: {} record(s) still failing",
+        elapsed.as_secs(),
+        failing_records.len()
+    )]
+    VerificationTimeout {
+        domain_id: String,
+        elapsed: Duration,
+        failing_records: Vec<DnsRecord>,
+    },
+
+    /// The API marked the domain as failed, so waiting longer will not help.
+    #[error(
+        "Domain {} failed verification: {} record(s) failing",
+        domain.name,
+        domain.missing_records().count()
+    )]
+    VerificationFailed { domain: Box<Domain> },
+
+    #[error(
+        "Campaign {campaign_id} still {status} after {}s",
+        elapsed.as_secs()
+    )]
+    CampaignTimeout {
+        campaign_id: String,
+        elapsed: Duration,
+        /// Status at the last check.
+        status: CampaignStatus,
+    },
+
+    #[error(
+        "Cannot {action} campaign {campaign_id} while it is {status} (allowed: {})",
+        if allowed.is_empty() {
+            "none".to_string()
+        } else {
+            allowed.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(", ")
+        }
+    )]
+    InvalidTransition {
+        campaign_id: String,
+        status: CampaignStatus,
+        action: CampaignAction,
+        /// Actions that are valid from `status`.
+        allowed: Vec<CampaignAction>,
+    },
+
+    /// Input rejected by a check in this crate, before any request was sent.
+    /// Errors from the API itself are reported as [`VeilMailError::Validation`].
+    #[error("Invalid {field}: {message}")]
+    InvalidInput { field: String, message: String },
+
+    #[error("Invalid webhook signature")]
+    InvalidSignature,
+
+    /// A signed link was malformed or its signature did not match.
+    #[error("Invalid link: {0}")]
+    InvalidLink(String),
+
+    /// A signed link was genuine but has expired. The IDs can be trusted,
+    /// e.g. to offer a fresh link.
+    #[error("Link for subscriber {subscriber_id} in audience {audience_id} has expired")]
+    LinkExpired {
+        audience_id: String,
+        subscriber_id: String,
+    },
+
+    #[error("Template error: {0}")]
+    Template(String),
+
+    #[error(
+        "Template {template_id} does not match its data type: missing [{}], unknown [{}]",
+        missing.join(", "),
+        unknown.join(", ")
+    )]
+    TemplateMismatch {
+        template_id: String,
+        /// Variables the template needs that the type does not provide.
+        missing: Vec<String>,
+        /// Fields of the type the template does not use.
+        unknown: Vec<String>,
+    },
+
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
@@ -61,6 +147,13 @@ pub enum VeilMailError {
 }
 
 impl VeilMailError {
+    pub(crate) fn invalid_input(field: impl Into<String>, message: impl Into<String>) -> Self {
+        VeilMailError::InvalidInput {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
     pub(crate) fn from_response(status: u16, body: &serde_json::Value) -> Self {
         let error = body.get("error").unwrap_or(body);
         let message = error
@@ -82,7 +175,7 @@ impl VeilMailError {
                     .and_then(|v| serde_json::from_value(v.clone()).ok()),
             },
             422 => {
-                let pii_types = error
+                let pii_types: Vec<String> = error
                     .get("piiTypes")
                     .and_then(|v| v.as_array())
                     .map(|arr| {
@@ -1,6 +1,5 @@
 use reqwest::Client;
 use serde_json::Value;
-use std::collections::HashMap;
 use std::time::Duration;
 
 use crate::error::{Result, VeilMailError};
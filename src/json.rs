@@ -0,0 +1,16 @@
+//! Helpers for working with untyped JSON values.
+
+use serde_json::Value;
+
+/// The kind of a JSON value with an article, for error messages, e.g.
+/// "an array".
+pub(crate) fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
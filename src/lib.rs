@@ -21,11 +21,27 @@
 //! }
 //! ```
 
+mod csv;
+pub mod dns;
 pub mod error;
 mod http;
+mod json;
+pub mod links;
+pub mod pagination;
+pub mod polling;
+pub mod render;
+pub mod reports;
 pub mod resources;
+pub mod schedule;
+pub mod sequences;
+pub mod subscribers;
+pub mod sync;
 pub mod webhook;
 
+pub use resources::templates::VeilTemplate;
+#[cfg(feature = "derive")]
+pub use veilmail_derive::VeilTemplate;
+
 use error::Result;
 use http::HttpClient;
 use resources::{
@@ -0,0 +1,314 @@
+//! Signed unsubscribe and preference-center links.
+//!
+//! A [`LinkSigner`] puts the audience, subscriber, purpose and an expiry in a
+//! URL's query string and signs them with HMAC-SHA256, the same primitive
+//! used for webhook signatures. When the link comes back to your own page,
+//! [`LinkSigner::verify`] checks it and yields a [`VerifiedLink`] that can
+//! unsubscribe the subscriber or read and update their topic preferences.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail, request_url: &str) -> veilmail::error::Result<()> {
+//! use veilmail::links::LinkSigner;
+//!
+//! let signer = LinkSigner::new("a-long-random-secret", "https://example.com/email/preferences")?;
+//!
+//! // When sending:
+//! let url = signer.preferences_url("audience_xxxxx", "sub_xxxxx")?;
+//!
+//! // When the subscriber clicks it:
+//! let link = signer.verify(request_url)?;
+//! let center = link.preference_center(&client.topics()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::Url;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, VeilMailError};
+use crate::resources::audiences::{Audiences, Subscriber};
+use crate::resources::topics::{PreferenceCenter, PreferenceUpdate, Topics};
+use crate::webhook::{sign, verify_signature};
+
+/// How long links stay valid unless [`LinkSigner::ttl`] is set.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 24 * 3_600);
+
+const AUDIENCE_PARAM: &str = "audience";
+const SUBSCRIBER_PARAM: &str = "subscriber";
+const PURPOSE_PARAM: &str = "purpose";
+const EXPIRES_PARAM: &str = "expires";
+const SIGNATURE_PARAM: &str = "signature";
+
+/// What a link lets its holder do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkPurpose {
+    /// Unsubscribe from the audience.
+    Unsubscribe,
+    /// View and change topic preferences, or unsubscribe.
+    Preferences,
+}
+
+impl LinkPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkPurpose::Unsubscribe => "unsubscribe",
+            LinkPurpose::Preferences => "preferences",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "unsubscribe" => Some(LinkPurpose::Unsubscribe),
+            "preferences" => Some(LinkPurpose::Preferences),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LinkPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Generates and verifies signed links pointing at your own page.
+#[derive(Clone)]
+pub struct LinkSigner {
+    secret: String,
+    base_url: Url,
+    ttl: Duration,
+}
+
+impl fmt::Debug for LinkSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkSigner")
+            .field("base_url", &self.base_url.as_str())
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LinkSigner {
+    /// `base_url` is the page that handles the links; any query string it
+    /// already has is kept. Use a long random `secret` that is separate from
+    /// your API key and webhook secret.
+    pub fn new(secret: impl Into<String>, base_url: &str) -> Result<Self> {
+        let secret = secret.into();
+        if secret.is_empty() {
+            return Err(VeilMailError::Other(
+                "link signing secret must not be empty".to_string(),
+            ));
+        }
+        let base_url = Url::parse(base_url)
+            .map_err(|e| VeilMailError::Other(format!("invalid link base URL: {}", e)))?;
+        Ok(Self {
+            secret,
+            base_url,
+            ttl: DEFAULT_TTL,
+        })
+    }
+
+    /// How long new links stay valid. A TTL too large to add to the current
+    /// time makes [`LinkSigner::url`] fail with [`VeilMailError::InvalidLink`].
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn unsubscribe_url(&self, audience_id: &str, subscriber_id: &str) -> Result<String> {
+        self.url(LinkPurpose::Unsubscribe, audience_id, subscriber_id)
+    }
+
+    pub fn preferences_url(&self, audience_id: &str, subscriber_id: &str) -> Result<String> {
+        self.url(LinkPurpose::Preferences, audience_id, subscriber_id)
+    }
+
+    /// A signed link for `purpose` that expires after the signer's TTL.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use veilmail::error::VeilMailError;
+    /// use veilmail::links::{LinkPurpose, LinkSigner};
+    ///
+    /// let signer = LinkSigner::new("secret", "https://example.com/prefs")
+    ///     .unwrap()
+    ///     .ttl(Duration::MAX);
+    /// let url = signer.url(LinkPurpose::Preferences, "audience_1", "sub_1");
+    /// assert!(matches!(url, Err(VeilMailError::InvalidLink(_))));
+    /// ```
+    pub fn url(
+        &self,
+        purpose: LinkPurpose,
+        audience_id: &str,
+        subscriber_id: &str,
+    ) -> Result<String> {
+        let expires = SystemTime::now()
+            .checked_add(self.ttl)
+            .ok_or_else(|| invalid(format!("TTL of {:?} is too large", self.ttl)))?;
+        let expires = unix_seconds(expires)?;
+        let signature = sign(
+            payload(purpose, audience_id, subscriber_id, expires),
+            &self.secret,
+        );
+
+        let mut url = self.base_url.clone();
+        url.query_pairs_mut()
+            .append_pair(AUDIENCE_PARAM, audience_id)
+            .append_pair(SUBSCRIBER_PARAM, subscriber_id)
+            .append_pair(PURPOSE_PARAM, purpose.as_str())
+            .append_pair(EXPIRES_PARAM, &expires.to_string())
+            .append_pair(SIGNATURE_PARAM, &signature);
+        Ok(url.into())
+    }
+
+    /// Check a link made by this signer. Accepts the full URL or just the
+    /// path and query of the incoming request.
+    ///
+    /// Fails with [`VeilMailError::InvalidLink`] if the link was altered or
+    /// is incomplete, and [`VeilMailError::LinkExpired`] if it is genuine
+    /// but too old.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use veilmail::error::VeilMailError;
+    /// use veilmail::links::{LinkPurpose, LinkSigner};
+    ///
+    /// let signer = LinkSigner::new("secret", "https://example.com/prefs").unwrap();
+    /// let url = signer.unsubscribe_url("audience_1", "sub_1").unwrap();
+    ///
+    /// let link = signer.verify(&url).unwrap();
+    /// assert_eq!(link.purpose, LinkPurpose::Unsubscribe);
+    /// assert_eq!(link.subscriber_id, "sub_1");
+    ///
+    /// let tampered = url.replace("sub_1", "sub_2");
+    /// assert!(matches!(signer.verify(&tampered), Err(VeilMailError::InvalidLink(_))));
+    ///
+    /// let expired = signer.clone().ttl(Duration::ZERO).unsubscribe_url("audience_1", "sub_1").unwrap();
+    /// assert!(matches!(signer.verify(&expired), Err(VeilMailError::LinkExpired { .. })));
+    /// ```
+    pub fn verify(&self, url: &str) -> Result<VerifiedLink> {
+        // Absolute URLs replace the base; paths are resolved against it.
+        let url = self
+            .base_url
+            .join(url)
+            .map_err(|e| invalid(format!("cannot parse URL: {}", e)))?;
+
+        let mut audience_id = None;
+        let mut subscriber_id = None;
+        let mut purpose = None;
+        let mut expires = None;
+        let mut signature = None;
+        for (key, value) in url.query_pairs() {
+            let slot = match key.as_ref() {
+                AUDIENCE_PARAM => &mut audience_id,
+                SUBSCRIBER_PARAM => &mut subscriber_id,
+                PURPOSE_PARAM => &mut purpose,
+                EXPIRES_PARAM => &mut expires,
+                SIGNATURE_PARAM => &mut signature,
+                _ => continue,
+            };
+            if slot.replace(value.into_owned()).is_some() {
+                return Err(invalid(format!("`{}` appears more than once", key)));
+            }
+        }
+        let required = |value: Option<String>, name: &str| {
+            value.ok_or_else(|| invalid(format!("missing `{}`", name)))
+        };
+        let audience_id = required(audience_id, AUDIENCE_PARAM)?;
+        let subscriber_id = required(subscriber_id, SUBSCRIBER_PARAM)?;
+        let purpose = required(purpose, PURPOSE_PARAM)?;
+        let purpose = LinkPurpose::parse(&purpose)
+            .ok_or_else(|| invalid(format!("unknown purpose `{}`", purpose)))?;
+        let expires: u64 = required(expires, EXPIRES_PARAM)?
+            .parse()
+            .map_err(|_| invalid(format!("`{}` is not a timestamp", EXPIRES_PARAM)))?;
+        let signature = required(signature, SIGNATURE_PARAM)?;
+
+        let payload = payload(purpose, &audience_id, &subscriber_id, expires);
+        if !verify_signature(&payload, &signature, &self.secret) {
+            return Err(invalid("signature does not match".to_string()));
+        }
+        if expires <= unix_seconds(SystemTime::now())? {
+            return Err(VeilMailError::LinkExpired {
+                audience_id,
+                subscriber_id,
+            });
+        }
+
+        Ok(VerifiedLink {
+            purpose,
+            audience_id,
+            subscriber_id,
+            expires_at: UNIX_EPOCH + Duration::from_secs(expires),
+        })
+    }
+}
+
+/// A link that passed [`LinkSigner::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedLink {
+    pub purpose: LinkPurpose,
+    pub audience_id: String,
+    pub subscriber_id: String,
+    pub expires_at: SystemTime,
+}
+
+impl VerifiedLink {
+    /// Unsubscribe the subscriber from the audience. Allowed for both
+    /// purposes, so a preferences page can offer "unsubscribe from all".
+    pub async fn unsubscribe(&self, audiences: &Audiences<'_>) -> Result<Subscriber> {
+        audiences
+            .subscribers(&self.audience_id)
+            .unsubscribe(&self.subscriber_id)
+            .await
+    }
+
+    /// The subscriber's topic choices. Only for preference links.
+    pub async fn preference_center(&self, topics: &Topics<'_>) -> Result<PreferenceCenter> {
+        self.require(LinkPurpose::Preferences)?;
+        topics
+            .preference_center(&self.audience_id, &self.subscriber_id)
+            .await
+    }
+
+    /// Apply `update` to the subscriber's current choices with a single
+    /// write. Only for preference links.
+    pub async fn apply_preferences(
+        &self,
+        topics: &Topics<'_>,
+        update: &PreferenceUpdate,
+    ) -> Result<PreferenceCenter> {
+        let center = self.preference_center(topics).await?;
+        topics.apply_preferences(&center, update).await
+    }
+
+    fn require(&self, purpose: LinkPurpose) -> Result<()> {
+        if self.purpose == purpose {
+            Ok(())
+        } else {
+            Err(invalid(format!(
+                "expected a {} link, got {}",
+                purpose, self.purpose
+            )))
+        }
+    }
+}
+
+/// The signed string. JSON-encoding the fields keeps the boundaries between
+/// them unambiguous whatever characters the IDs contain.
+fn payload(purpose: LinkPurpose, audience_id: &str, subscriber_id: &str, expires: u64) -> String {
+    serde_json::json!(["v1", purpose.as_str(), audience_id, subscriber_id, expires]).to_string()
+}
+
+fn unix_seconds(at: SystemTime) -> Result<u64> {
+    at.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|_| VeilMailError::Other("system clock is before 1970".to_string()))
+}
+
+fn invalid(reason: String) -> VeilMailError {
+    VeilMailError::InvalidLink(reason)
+}
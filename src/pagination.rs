@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// One page of a cursor-paginated list.
+///
+/// Pass `next_cursor` as the `cursor` of the next request while `has_more`
+/// is true.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// The cursor for the next page, if there is one.
+    pub fn next(&self) -> Option<&str> {
+        if self.has_more {
+            self.next_cursor.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
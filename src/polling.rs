@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+/// How often and for how long to poll the API while waiting for a state
+/// change.
+///
+/// The interval starts at `initial_interval` and grows by `multiplier` after
+/// every attempt, up to `max_interval`. Waiting stops after `timeout`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use veilmail::polling::PollPolicy;
+///
+/// let policy = PollPolicy::new(Duration::from_secs(15 * 60))
+///     .initial_interval(Duration::from_secs(10))
+///     .max_interval(Duration::from_secs(120));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollPolicy {
+    pub initial_interval: Duration,
+    pub max_interval: Duration,
+    pub multiplier: f64,
+    pub timeout: Duration,
+}
+
+impl Default for PollPolicy {
+    /// Poll every 5 seconds, backing off to once a minute, for 10 minutes.
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_secs(5),
+            max_interval: Duration::from_secs(60),
+            multiplier: 1.5,
+            timeout: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl PollPolicy {
+    /// The default policy with a custom timeout.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            ..Self::default()
+        }
+    }
+
+    pub fn initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    pub fn max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Factor applied to the interval after each attempt. Use `1.0` for a
+    /// fixed interval. Values below `1.0` or NaN count as `1.0`.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub(crate) fn next_interval(&self, current: Duration) -> Duration {
+        // `max` also maps NaN to 1.0; an overflowing or infinite product
+        // fails the conversion and is capped like any other large interval.
+        let multiplier = self.multiplier.max(1.0);
+        Duration::try_from_secs_f64(current.as_secs_f64() * multiplier)
+            .map_or(self.max_interval, |next| next.min(self.max_interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_interval_grows_and_caps() {
+        let policy = PollPolicy::default();
+        assert_eq!(
+            policy.next_interval(Duration::from_secs(10)),
+            Duration::from_secs(15)
+        );
+        assert_eq!(
+            policy.next_interval(Duration::from_secs(50)),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn next_interval_handles_degenerate_multipliers() {
+        let current = Duration::from_secs(10);
+        for multiplier in [f64::INFINITY, f64::MAX, 1e300] {
+            let policy = PollPolicy::default().multiplier(multiplier);
+            assert_eq!(policy.next_interval(current), policy.max_interval);
+        }
+        for multiplier in [f64::NAN, 0.5, -3.0, f64::NEG_INFINITY] {
+            let policy = PollPolicy::default().multiplier(multiplier);
+            assert_eq!(policy.next_interval(current), current);
+        }
+        let policy = PollPolicy::default().multiplier(f64::INFINITY);
+        assert_eq!(policy.next_interval(Duration::ZERO), policy.max_interval);
+    }
+}
@@ -0,0 +1,567 @@
+//! Local rendering of Veil Mail templates.
+//!
+//! Supports the same variable syntax as the server, so `templateData` can be
+//! rendered and checked offline:
+//!
+//! * `{{ name }}` — a variable, HTML-escaped in HTML bodies
+//! * `{{ user.first_name }}` — a nested field
+//! * `{{{ raw_html }}}` — a variable inserted without escaping
+//! * `{{#if var}} ... {{else}} ... {{/if}}` — a conditional section
+//! * `{{#each items}} ... {{/each}}` — a loop, where `{{this}}` is the current
+//!   item, `{{field}}` one of its fields, and `{{@index}}` its position
+//! * `{{! comment }}` — ignored
+//!
+//! Missing variables render as empty strings, as they do on the server; use
+//! [`TemplateSource::missing_variables`] to catch them.
+//!
+//! # Example
+//!
+//! ```
+//! use serde_json::json;
+//! use veilmail::render::TemplateSource;
+//!
+//! let source = TemplateSource::parse("<p>Hi {{ name }}, you have {{ count }} messages</p>")?;
+//! let data = json!({ "name": "Alice & Bob" });
+//!
+//! assert_eq!(source.render_html(&data), "<p>Hi Alice &amp; Bob, you have  messages</p>");
+//! assert_eq!(source.missing_variables(&data), ["count"]);
+//! # Ok::<(), veilmail::error::VeilMailError>(())
+//! ```
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::error::{Result, VeilMailError};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var {
+        path: String,
+        escape: bool,
+    },
+    If {
+        path: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+    },
+}
+
+/// A parsed template body, subject, or text part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateSource {
+    nodes: Vec<Node>,
+}
+
+impl TemplateSource {
+    /// Parse template source, failing on unbalanced or malformed tags.
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut pos = 0;
+        let nodes = parse_nodes(&tokens, &mut pos, None)?;
+        Ok(Self { nodes })
+    }
+
+    /// Top-level variables the template references, including `if` and
+    /// `each` targets. Fields used inside an `each` loop are not included.
+    pub fn variables(&self) -> BTreeSet<String> {
+        let mut out = BTreeSet::new();
+        collect_variables(&self.nodes, &mut out);
+        out
+    }
+
+    /// Render with HTML escaping of `{{ }}` values.
+    pub fn render_html(&self, data: &Value) -> String {
+        self.render_with(data, true, &mut Vec::new())
+    }
+
+    /// Render without escaping, for subjects and plain-text bodies.
+    pub fn render_text(&self, data: &Value) -> String {
+        self.render_with(data, false, &mut Vec::new())
+    }
+
+    /// Variables that would render blank with this data.
+    ///
+    /// Only variables on the path actually taken are reported, so a variable
+    /// inside a false `{{#if}}` branch is not missing.
+    pub fn missing_variables(&self, data: &Value) -> Vec<String> {
+        let mut missing = Vec::new();
+        self.render_with(data, false, &mut missing);
+        missing
+    }
+
+    fn render_with(&self, data: &Value, escape_html: bool, missing: &mut Vec<String>) -> String {
+        let mut out = String::new();
+        let mut ctx = Context {
+            root: data,
+            scopes: Vec::new(),
+            escape_html,
+            missing,
+        };
+        render_nodes(&self.nodes, &mut ctx, &mut out);
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Var { path: String, escape: bool },
+    Open { block: String, path: String },
+    Else,
+    Close(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start..];
+
+        let (inner, raw, len) = if let Some(body) = after.strip_prefix("{{{") {
+            let end = body
+                .find("}}}")
+                .ok_or_else(|| template_error("unclosed '{{{' tag"))?;
+            (&body[..end], true, end + 6)
+        } else {
+            let body = &after[2..];
+            let end = body
+                .find("}}")
+                .ok_or_else(|| template_error("unclosed '{{' tag"))?;
+            (&body[..end], false, end + 4)
+        };
+        let inner = inner.trim();
+
+        if raw {
+            tokens.push(Token::Var {
+                path: parse_path(inner)?,
+                escape: false,
+            });
+        } else if inner.starts_with('!') {
+            // Comment.
+        } else if let Some(block) = inner.strip_prefix('#') {
+            let (name, arg) = block.split_once(char::is_whitespace).ok_or_else(|| {
+                template_error(&format!("'{{{{#{}}}}}' needs an argument", block))
+            })?;
+            if name != "if" && name != "each" {
+                return Err(template_error(&format!("unknown block '#{}'", name)));
+            }
+            tokens.push(Token::Open {
+                block: name.to_string(),
+                path: parse_path(arg.trim())?,
+            });
+        } else if let Some(block) = inner.strip_prefix('/') {
+            tokens.push(Token::Close(block.trim().to_string()));
+        } else if inner == "else" {
+            tokens.push(Token::Else);
+        } else {
+            tokens.push(Token::Var {
+                path: parse_path(inner)?,
+                escape: true,
+            });
+        }
+
+        rest = &after[len..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+fn parse_path(path: &str) -> Result<String> {
+    let valid = !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'));
+    if valid {
+        Ok(path.to_string())
+    } else {
+        Err(template_error(&format!("invalid variable name '{}'", path)))
+    }
+}
+
+/// Parse nodes until the closing tag of `block` (or the end of input).
+fn parse_nodes(tokens: &[Token], pos: &mut usize, block: Option<&str>) -> Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Var { path, escape } => nodes.push(Node::Var {
+                path: path.clone(),
+                escape: *escape,
+            }),
+            Token::Open { block: name, path } => {
+                let body = parse_nodes(tokens, pos, Some(name))?;
+                let node = if name == "if" {
+                    let otherwise = if matches!(tokens.get(*pos - 1), Some(Token::Else)) {
+                        let otherwise = parse_nodes(tokens, pos, Some("if"))?;
+                        if matches!(tokens.get(*pos - 1), Some(Token::Else)) {
+                            return Err(template_error("more than one '{{else}}' in '{{#if}}'"));
+                        }
+                        otherwise
+                    } else {
+                        Vec::new()
+                    };
+                    Node::If {
+                        path: path.clone(),
+                        then: body,
+                        otherwise,
+                    }
+                } else {
+                    Node::Each {
+                        path: path.clone(),
+                        body,
+                    }
+                };
+                nodes.push(node);
+            }
+            Token::Else if block == Some("if") => return Ok(nodes),
+            Token::Else => return Err(template_error("'{{else}}' outside of '{{#if}}'")),
+            Token::Close(name) if Some(name.as_str()) == block => return Ok(nodes),
+            Token::Close(name) => {
+                return Err(template_error(&format!("unexpected '{{{{/{}}}}}'", name)))
+            }
+        }
+    }
+
+    match block {
+        Some(name) => Err(template_error(&format!("unclosed '{{{{#{}}}}}'", name))),
+        None => Ok(nodes),
+    }
+}
+
+fn collect_variables(nodes: &[Node], out: &mut BTreeSet<String>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Var { path, .. } => {
+                if !path.starts_with('@') && path != "this" {
+                    out.insert(path.clone());
+                }
+            }
+            Node::If {
+                path,
+                then,
+                otherwise,
+            } => {
+                out.insert(path.clone());
+                collect_variables(then, out);
+                collect_variables(otherwise, out);
+            }
+            Node::Each { path, .. } => {
+                out.insert(path.clone());
+            }
+        }
+    }
+}
+
+struct Scope<'a> {
+    item: &'a Value,
+    index: usize,
+}
+
+struct Context<'a, 'm> {
+    root: &'a Value,
+    scopes: Vec<Scope<'a>>,
+    escape_html: bool,
+    missing: &'m mut Vec<String>,
+}
+
+impl<'a> Context<'a, '_> {
+    fn lookup(&self, path: &str) -> Option<&'a Value> {
+        if let Some(scope) = self.scopes.last() {
+            if path == "this" {
+                return Some(scope.item);
+            }
+            if let Some(field) = path.strip_prefix("this.") {
+                return resolve(scope.item, field);
+            }
+            if let Some(value) = resolve(scope.item, path) {
+                return Some(value);
+            }
+        }
+        resolve(self.root, path)
+    }
+
+    fn note_missing(&mut self, path: &str) {
+        // Fields of loop items are data-dependent, not template variables.
+        if self.scopes.is_empty() && !self.missing.iter().any(|m| m == path) {
+            self.missing.push(path.to_string());
+        }
+    }
+}
+
+fn resolve<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |v, key| match v {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+        .filter(|v| !v.is_null())
+}
+
+fn render_nodes<'a>(nodes: &'a [Node], ctx: &mut Context<'a, '_>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { path, escape } => {
+                if path == "@index" {
+                    if let Some(scope) = ctx.scopes.last() {
+                        out.push_str(&scope.index.to_string());
+                    }
+                    continue;
+                }
+                match ctx.lookup(path) {
+                    Some(value) => {
+                        let text = stringify(value);
+                        if *escape && ctx.escape_html {
+                            out.push_str(&escape_html(&text));
+                        } else {
+                            out.push_str(&text);
+                        }
+                    }
+                    None => ctx.note_missing(path),
+                }
+            }
+            Node::If {
+                path,
+                then,
+                otherwise,
+            } => {
+                let branch = if ctx.lookup(path).is_some_and(truthy) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, ctx, out);
+            }
+            Node::Each { path, body } => match ctx.lookup(path) {
+                Some(Value::Array(items)) => {
+                    for (index, item) in items.iter().enumerate() {
+                        ctx.scopes.push(Scope { item, index });
+                        render_nodes(body, ctx, out);
+                        ctx.scopes.pop();
+                    }
+                }
+                Some(_) => {}
+                None => ctx.note_missing(path),
+            },
+        }
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn stringify(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn template_error(message: &str) -> VeilMailError {
+    VeilMailError::Template(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn html(source: &str, data: Value) -> String {
+        TemplateSource::parse(source).unwrap().render_html(&data)
+    }
+
+    fn parse_error(source: &str) -> String {
+        match TemplateSource::parse(source) {
+            Err(VeilMailError::Template(message)) => message,
+            other => panic!(
+                "expected a template error for {:?}, got {:?}",
+                source, other
+            ),
+        }
+    }
+
+    #[test]
+    fn escapes_values_in_html_only() {
+        let source = TemplateSource::parse("{{ v }}|{{{ v }}}").unwrap();
+        let data = json!({ "v": "<a href=\"x\">Tom & 'Jerry'</a>" });
+        assert_eq!(
+            source.render_html(&data),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;|<a href=\"x\">Tom & 'Jerry'</a>"
+        );
+        assert_eq!(
+            source.render_text(&data),
+            "<a href=\"x\">Tom & 'Jerry'</a>|<a href=\"x\">Tom & 'Jerry'</a>"
+        );
+        // Literal text around tags is never escaped.
+        assert_eq!(html("<b>{{ n }}</b>", json!({ "n": 1 })), "<b>1</b>");
+    }
+
+    #[test]
+    fn resolves_nested_paths() {
+        let data = json!({
+            "user": { "name": { "first": "Ada" }, "tags": ["admin", "ops"] },
+            "count": 3,
+            "active": true,
+        });
+        assert_eq!(
+            html(
+                "{{user.name.first}} {{ user.tags.1 }} {{count}} {{active}}",
+                data.clone()
+            ),
+            "Ada ops 3 true"
+        );
+        let source = TemplateSource::parse("{{ user.name.last }}{{ user.tags.5 }}").unwrap();
+        assert_eq!(source.render_html(&data), "");
+        assert_eq!(
+            source.missing_variables(&data),
+            ["user.name.last", "user.tags.5"]
+        );
+    }
+
+    #[test]
+    fn renders_each_sections() {
+        let source = "{{#each items}}{{@index}}:{{name}}({{ this.sku }}) {{currency}}; {{/each}}";
+        let data = json!({
+            "currency": "EUR",
+            "items": [{ "name": "Pen", "sku": "p1" }, { "name": "<Ink>", "sku": "i2" }],
+        });
+        assert_eq!(html(source, data), "0:Pen(p1) EUR; 1:&lt;Ink&gt;(i2) EUR; ");
+        assert_eq!(
+            html(
+                "{{#each tags}}[{{this}}]{{/each}}",
+                json!({ "tags": ["a", "b"] })
+            ),
+            "[a][b]"
+        );
+        assert_eq!(
+            html("{{#each tags}}x{{/each}}", json!({ "tags": "not a list" })),
+            ""
+        );
+    }
+
+    #[test]
+    fn renders_conditionals() {
+        let source = TemplateSource::parse("{{#if v}}yes{{else}}no{{/if}}").unwrap();
+        for (value, expected) in [
+            (json!(true), "yes"),
+            (json!("0"), "yes"),
+            (json!([1]), "yes"),
+            (json!({}), "yes"),
+            (json!(false), "no"),
+            (json!(0), "no"),
+            (json!(""), "no"),
+            (json!([]), "no"),
+            (json!(null), "no"),
+        ] {
+            assert_eq!(
+                source.render_html(&json!({ "v": value })),
+                expected,
+                "{}",
+                value
+            );
+        }
+        assert_eq!(source.render_html(&json!({})), "no");
+
+        let nested = "{{#each items}}{{#if done}}x{{else}}-{{/if}}{{/each}}";
+        let data = json!({ "items": [{ "done": true }, { "done": false }, {}] });
+        assert_eq!(html(nested, data), "x--");
+    }
+
+    #[test]
+    fn ignores_comments() {
+        assert_eq!(html("a{{! note }}b", json!({})), "ab");
+    }
+
+    #[test]
+    fn rejects_unclosed_and_mismatched_tags() {
+        assert_eq!(parse_error("Hi {{ name"), "unclosed '{{' tag");
+        assert_eq!(parse_error("Hi {{{ name }}"), "unclosed '{{{' tag");
+        assert_eq!(parse_error("{{#if a}}yes"), "unclosed '{{#if}}'");
+        assert_eq!(
+            parse_error("{{#each a}}{{#if b}}{{/each}}"),
+            "unexpected '{{/each}}'"
+        );
+        assert_eq!(parse_error("{{/if}}"), "unexpected '{{/if}}'");
+        assert_eq!(parse_error("{{else}}"), "'{{else}}' outside of '{{#if}}'");
+        assert_eq!(
+            parse_error("{{#if a}}1{{else}}2{{else}}3{{/if}}"),
+            "more than one '{{else}}' in '{{#if}}'"
+        );
+        assert_eq!(
+            parse_error("{{#if}}x{{/if}}"),
+            "'{{#if}}' needs an argument"
+        );
+        assert_eq!(
+            parse_error("{{#with a}}x{{/with}}"),
+            "unknown block '#with'"
+        );
+        assert_eq!(
+            parse_error("{{ first name }}"),
+            "invalid variable name 'first name'"
+        );
+        assert_eq!(parse_error("{{}}"), "invalid variable name ''");
+    }
+
+    #[test]
+    fn reports_missing_variables() {
+        let source = TemplateSource::parse(
+            "{{ name }} {{ name }} {{#if vip}}{{ perk }}{{else}}{{ offer }}{{/if}} \
+             {{#each items}}{{ sku }}{{/each}}{{#each orders}}{{/each}}",
+        )
+        .unwrap();
+        assert_eq!(
+            source.variables().into_iter().collect::<Vec<_>>(),
+            ["items", "name", "offer", "orders", "perk", "vip"]
+        );
+
+        // Only the branch taken counts, each name is reported once, and
+        // fields of loop items are not template variables.
+        let data = json!({ "vip": true, "items": [{}] });
+        assert_eq!(source.missing_variables(&data), ["name", "perk", "orders"]);
+
+        let data = json!({ "name": "Ada", "perk": "x", "offer": "y", "items": [], "orders": [] });
+        assert!(source.missing_variables(&data).is_empty());
+    }
+}
@@ -0,0 +1,343 @@
+//! Link-click reports for campaigns and emails.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! let links = client.campaigns().links("campaign_xxxxx", None).await?;
+//!
+//! // Merge links that differ only by UTM parameters, most clicked first.
+//! for link in links.strip_utm().sorted_by_clicks().iter().take(10) {
+//!     println!("{:>6} {}", link.total_clicks, link.url);
+//! }
+//!
+//! for domain in links.by_domain() {
+//!     println!("{}: {} clicks", domain.domain, domain.total_clicks);
+//! }
+//!
+//! std::fs::write("links.csv", links.to_csv()).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::csv;
+use crate::error::Result;
+use crate::json::value_kind;
+
+/// Clicks on one tracked link.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkReport {
+    pub url: String,
+    #[serde(default, alias = "clicks", alias = "totalClickCount")]
+    pub total_clicks: u64,
+    #[serde(default, alias = "uniqueClickCount")]
+    pub unique_clicks: u64,
+    /// ISO 8601 timestamp of the first click.
+    #[serde(
+        default,
+        alias = "firstClickedAt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub first_click: Option<String>,
+    /// ISO 8601 timestamp of the latest click.
+    #[serde(
+        default,
+        alias = "lastClickedAt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_click: Option<String>,
+}
+
+impl LinkReport {
+    /// The lowercased host of `url`, without port or credentials.
+    pub fn domain(&self) -> String {
+        domain(&self.url)
+    }
+
+    /// Fold `other` into this report. Unique clicks are summed, so the
+    /// result is an upper bound when the same person clicked both links.
+    fn merge(&mut self, other: &LinkReport) {
+        self.total_clicks += other.total_clicks;
+        self.unique_clicks += other.unique_clicks;
+        self.first_click = earliest(self.first_click.take(), other.first_click.clone());
+        self.last_click = latest(self.last_click.take(), other.last_click.clone());
+    }
+}
+
+/// Clicks on every link in a domain, from [`LinkReports::by_domain`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainReport {
+    pub domain: String,
+    pub total_clicks: u64,
+    /// Summed across links, so an upper bound on distinct clickers.
+    pub unique_clicks: u64,
+    pub links: Vec<LinkReport>,
+}
+
+/// The tracked links of a campaign or email, from
+/// [`Campaigns::links`](crate::resources::campaigns::Campaigns::links) or
+/// [`Emails::links`](crate::resources::emails::Emails::links).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LinkReports {
+    pub links: Vec<LinkReport>,
+}
+
+impl LinkReports {
+    /// Accepts an array of links, optionally under `links`, with or without
+    /// a `data` wrapper. Any other shape is a deserialization error rather
+    /// than an empty report.
+    pub(crate) fn from_response(response: Value) -> Result<Self> {
+        let response = match response {
+            Value::Object(mut object) if object.contains_key("data") => {
+                object.remove("data").unwrap_or_default()
+            }
+            other => other,
+        };
+        let links = match response {
+            Value::Array(_) => response,
+            Value::Object(mut object) if object.get("links").is_some_and(Value::is_array) => {
+                object.remove("links").unwrap_or_default()
+            }
+            other => {
+                return Err(serde_json::Error::custom(format!(
+                    "expected an array of links, got {}",
+                    value_kind(&other)
+                ))
+                .into())
+            }
+        };
+        Ok(Self {
+            links: serde_json::from_value(links)?,
+        })
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, LinkReport> {
+        self.links.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Total clicks across all links.
+    pub fn total_clicks(&self) -> u64 {
+        self.links.iter().map(|l| l.total_clicks).sum()
+    }
+
+    /// Most total clicks first; ties keep their order.
+    pub fn sorted_by_clicks(mut self) -> Self {
+        self.links.sort_by_key(|l| Reverse(l.total_clicks));
+        self
+    }
+
+    /// Most unique clicks first; ties keep their order.
+    pub fn sorted_by_unique_clicks(mut self) -> Self {
+        self.links.sort_by_key(|l| Reverse(l.unique_clicks));
+        self
+    }
+
+    /// Remove `utm_*` query parameters from every URL and merge links that
+    /// become identical, keeping the order of first appearance.
+    ///
+    /// ```
+    /// use veilmail::reports::links::{LinkReport, LinkReports};
+    ///
+    /// let link = |url: &str, clicks| LinkReport { url: url.into(), total_clicks: clicks, ..Default::default() };
+    /// let reports = LinkReports {
+    ///     links: vec![
+    ///         link("https://example.com/sale?utm_source=mail&id=7", 3),
+    ///         link("https://example.com/sale?id=7&utm_campaign=spring", 2),
+    ///     ],
+    /// };
+    ///
+    /// let merged = reports.strip_utm();
+    /// assert_eq!(merged.len(), 1);
+    /// assert_eq!(merged.links[0].url, "https://example.com/sale?id=7");
+    /// assert_eq!(merged.links[0].total_clicks, 5);
+    /// ```
+    pub fn strip_utm(&self) -> Self {
+        let mut links: Vec<LinkReport> = Vec::new();
+        let mut index: BTreeMap<String, usize> = BTreeMap::new();
+        for link in &self.links {
+            let url = strip_utm(&link.url);
+            match index.get(&url) {
+                Some(&i) => links[i].merge(link),
+                None => {
+                    index.insert(url.clone(), links.len());
+                    links.push(LinkReport {
+                        url,
+                        ..link.clone()
+                    });
+                }
+            }
+        }
+        Self { links }
+    }
+
+    /// Group links by [`domain`](LinkReport::domain), most clicked domain
+    /// first.
+    pub fn by_domain(&self) -> Vec<DomainReport> {
+        let mut domains: BTreeMap<String, DomainReport> = BTreeMap::new();
+        for link in &self.links {
+            let domain = link.domain();
+            let report = domains
+                .entry(domain.clone())
+                .or_insert_with(|| DomainReport {
+                    domain,
+                    ..DomainReport::default()
+                });
+            report.total_clicks += link.total_clicks;
+            report.unique_clicks += link.unique_clicks;
+            report.links.push(link.clone());
+        }
+        let mut domains: Vec<DomainReport> = domains.into_values().collect();
+        domains.sort_by_key(|d| Reverse(d.total_clicks));
+        domains
+    }
+
+    /// CSV with a header row: `url,total_clicks,unique_clicks,first_click,last_click`.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("url,total_clicks,unique_clicks,first_click,last_click\n");
+        for link in &self.links {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv::field(&link.url),
+                link.total_clicks,
+                link.unique_clicks,
+                csv::field(link.first_click.as_deref().unwrap_or("")),
+                csv::field(link.last_click.as_deref().unwrap_or("")),
+            ));
+        }
+        out
+    }
+}
+
+impl IntoIterator for LinkReports {
+    type Item = LinkReport;
+    type IntoIter = std::vec::IntoIter<LinkReport>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.links.into_iter()
+    }
+}
+
+impl<'r> IntoIterator for &'r LinkReports {
+    type Item = &'r LinkReport;
+    type IntoIter = std::slice::Iter<'r, LinkReport>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.links.iter()
+    }
+}
+
+/// `url` without `utm_*` query parameters (matched case-insensitively). The
+/// fragment and other parameters are kept in order.
+pub fn strip_utm(url: &str) -> String {
+    let (rest, fragment) = match url.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (url, None),
+    };
+    let Some((base, query)) = rest.split_once('?') else {
+        return url.to_string();
+    };
+
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let key = pair.split('=').next().unwrap_or("");
+            !pair.is_empty() && !key.to_ascii_lowercase().starts_with("utm_")
+        })
+        .collect();
+
+    let mut out = base.to_string();
+    if !kept.is_empty() {
+        out.push('?');
+        out.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        out.push('#');
+        out.push_str(fragment);
+    }
+    out
+}
+
+fn domain(url: &str) -> String {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = after_scheme.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        // IPv6 literal
+        Some(v6) => v6.split(']').next().unwrap_or(v6),
+        None => host.split(':').next().unwrap_or(host),
+    };
+    host.to_ascii_lowercase()
+}
+
+// ISO 8601 timestamps in the same format compare correctly as strings.
+fn earliest(a: Option<String>, b: Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn latest(a: Option<String>, b: Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::VeilMailError;
+    use serde_json::json;
+
+    #[test]
+    fn from_response_accepts_known_shapes() {
+        let link = json!({"url": "https://example.com", "clicks": 3});
+        for response in [
+            json!([link]),
+            json!({"data": [link]}),
+            json!({"links": [link]}),
+            json!({"data": {"links": [link]}}),
+        ] {
+            let reports = LinkReports::from_response(response.clone()).unwrap();
+            assert_eq!(reports.total_clicks(), 3, "{}", response);
+        }
+    }
+
+    #[test]
+    fn from_response_rejects_unexpected_shapes() {
+        for response in [
+            json!({}),
+            json!({"data": {}}),
+            json!({"data": {"items": []}}),
+            json!({"links": {"url": "https://example.com"}}),
+            json!(null),
+        ] {
+            let result = LinkReports::from_response(response.clone());
+            assert!(
+                matches!(result, Err(VeilMailError::Json(_))),
+                "{}",
+                response
+            );
+        }
+    }
+}
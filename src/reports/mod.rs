@@ -0,0 +1,3 @@
+//! Typed analytics reports.
+
+pub mod links;
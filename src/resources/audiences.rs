@@ -1,7 +1,184 @@
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt;
 
 use crate::error::Result;
 use crate::http::HttpClient;
+use crate::pagination::Page;
+
+/// A list of subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Audience {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscriber_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Lifecycle status of a subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriberStatus {
+    /// Awaiting double opt-in confirmation.
+    #[default]
+    Pending,
+    Active,
+    Unsubscribed,
+    Bounced,
+    Complained,
+    /// A status this SDK does not know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+impl SubscriberStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriberStatus::Pending => "pending",
+            SubscriberStatus::Active => "active",
+            SubscriberStatus::Unsubscribed => "unsubscribed",
+            SubscriberStatus::Bounced => "bounced",
+            SubscriberStatus::Complained => "complained",
+            SubscriberStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Whether mail can be sent to a subscriber in this status.
+    pub fn is_mailable(&self) -> bool {
+        *self == SubscriberStatus::Active
+    }
+}
+
+impl fmt::Display for SubscriberStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A member of an audience.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscriber {
+    pub id: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub status: SubscriberStatus,
+    /// How consent was collected, e.g. `form` or `import`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consent_source: Option<String>,
+    /// When consent was given (ISO 8601).
+    #[serde(
+        default,
+        alias = "consentedAt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub consent_timestamp: Option<String>,
+    /// Engagement score from 0 to 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engagement_score: Option<f64>,
+    /// Custom properties.
+    #[serde(default, alias = "customProperties", skip_serializing_if = "Map::is_empty")]
+    pub properties: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl Subscriber {
+    /// First and last name joined, if either is set.
+    pub fn full_name(&self) -> Option<String> {
+        match (&self.first_name, &self.last_name) {
+            (Some(first), Some(last)) => Some(format!("{} {}", first, last)),
+            (Some(name), None) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Query for [`Subscribers::list`].
+///
+/// # Example
+///
+/// ```
+/// use veilmail::resources::audiences::{SubscriberFilter, SubscriberStatus};
+///
+/// let filter = SubscriberFilter::new()
+///     .status(SubscriberStatus::Active)
+///     .search("@example.com")
+///     .limit(100);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubscriberFilter {
+    pub status: Option<SubscriberStatus>,
+    /// Matches email addresses and names.
+    pub search: Option<String>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+    /// Additional query parameters passed through as-is.
+    pub extra: Vec<(String, String)>,
+}
+
+impl SubscriberFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(mut self, status: SubscriberStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn search(mut self, search: impl Into<String>) -> Self {
+        self.search = Some(search.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Add a query parameter this builder has no method for.
+    pub fn param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra.push((key.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn query(&self) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+        if let Some(status) = self.status {
+            query.push(("status".to_string(), status.as_str().to_string()));
+        }
+        if let Some(search) = &self.search {
+            query.push(("search".to_string(), search.clone()));
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit".to_string(), limit.to_string()));
+        }
+        if let Some(cursor) = &self.cursor {
+            query.push(("cursor".to_string(), cursor.clone()));
+        }
+        query.extend(self.extra.iter().cloned());
+        query
+    }
+}
 
 /// Audience management.
 pub struct Audiences<'a> {
@@ -9,29 +186,30 @@ pub struct Audiences<'a> {
 }
 
 impl<'a> Audiences<'a> {
-    pub async fn create(&self, params: Value) -> Result<Value> {
+    pub async fn create(&self, params: Value) -> Result<Audience> {
         let response = self.http.post("/v1/audiences", Some(&params)).await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http.get("/v1/audiences", params).await
+    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Page<Audience>> {
+        let response = self.http.get("/v1/audiences", params).await?;
+        Ok(serde_json::from_value(response)?)
     }
 
-    pub async fn get(&self, id: &str) -> Result<Value> {
+    pub async fn get(&self, id: &str) -> Result<Audience> {
         let response = self
             .http
             .get(&format!("/v1/audiences/{}", id), None)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn update(&self, id: &str, params: Value) -> Result<Value> {
+    pub async fn update(&self, id: &str, params: Value) -> Result<Audience> {
         let response = self
             .http
             .put(&format!("/v1/audiences/{}", id), &params)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn delete(&self, id: &str) -> Result<()> {
@@ -70,29 +248,35 @@ impl<'a> Audiences<'a> {
 
 /// Subscriber management within an audience.
 pub struct Subscribers<'a> {
-    http: &'a HttpClient,
-    base_path: String,
+    pub(crate) http: &'a HttpClient,
+    pub(crate) base_path: String,
 }
 
 impl<'a> Subscribers<'a> {
-    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http.get(&self.base_path, params).await
+    pub async fn list(&self, filter: &SubscriberFilter) -> Result<Page<Subscriber>> {
+        let query = filter.query();
+        let params: Vec<(&str, &str)> = query
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let response = self.http.get(&self.base_path, Some(&params)).await?;
+        Ok(serde_json::from_value(response)?)
     }
 
-    pub async fn add(&self, params: Value) -> Result<Value> {
+    pub async fn add(&self, params: Value) -> Result<Subscriber> {
         let response = self.http.post(&self.base_path, Some(&params)).await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn get(&self, subscriber_id: &str) -> Result<Value> {
+    pub async fn get(&self, subscriber_id: &str) -> Result<Subscriber> {
         let response = self
             .http
             .get(&format!("{}/{}", self.base_path, subscriber_id), None)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn update(&self, subscriber_id: &str, params: Value) -> Result<Value> {
+    pub async fn update(&self, subscriber_id: &str, params: Value) -> Result<Subscriber> {
         let response = self
             .http
             .put(
@@ -100,7 +284,7 @@ impl<'a> Subscribers<'a> {
                 &params,
             )
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn remove(&self, subscriber_id: &str) -> Result<()> {
@@ -109,7 +293,7 @@ impl<'a> Subscribers<'a> {
             .await
     }
 
-    pub async fn confirm(&self, subscriber_id: &str) -> Result<Value> {
+    pub async fn confirm(&self, subscriber_id: &str) -> Result<Subscriber> {
         let response = self
             .http
             .post(
@@ -117,7 +301,16 @@ impl<'a> Subscribers<'a> {
                 None,
             )
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
+    }
+
+    /// Mark a subscriber as unsubscribed without removing them.
+    pub async fn unsubscribe(&self, subscriber_id: &str) -> Result<Subscriber> {
+        self.update(
+            subscriber_id,
+            json!({ "status": SubscriberStatus::Unsubscribed }),
+        )
+        .await
     }
 
     pub async fn import(&self, params: Value) -> Result<Value> {
@@ -1,7 +1,586 @@
-use serde_json::Value;
+use futures_util::stream::{self, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::{Duration, Instant};
 
-use crate::error::Result;
+use crate::error::{Result, VeilMailError};
 use crate::http::HttpClient;
+use crate::pagination::Page;
+use crate::polling::PollPolicy;
+use crate::reports::links::LinkReports;
+use crate::schedule::SendAt;
+
+/// Lifecycle status of a campaign.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignStatus {
+    #[default]
+    Draft,
+    Scheduled,
+    Sending,
+    Paused,
+    Sent,
+    Cancelled,
+    /// A status this SDK does not know about yet. Transitions from it are not
+    /// checked locally.
+    #[serde(other)]
+    Unknown,
+}
+
+impl CampaignStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CampaignStatus::Draft => "draft",
+            CampaignStatus::Scheduled => "scheduled",
+            CampaignStatus::Sending => "sending",
+            CampaignStatus::Paused => "paused",
+            CampaignStatus::Sent => "sent",
+            CampaignStatus::Cancelled => "cancelled",
+            CampaignStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Whether the campaign can no longer change.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, CampaignStatus::Sent | CampaignStatus::Cancelled)
+    }
+
+    /// The status `action` leads to, or `None` if it is not allowed from
+    /// this status.
+    ///
+    /// ```
+    /// use veilmail::resources::campaigns::{CampaignAction, CampaignStatus};
+    ///
+    /// assert_eq!(CampaignStatus::Paused.transition(CampaignAction::Resume), Some(CampaignStatus::Sending));
+    /// assert_eq!(CampaignStatus::Draft.transition(CampaignAction::Resume), None);
+    /// ```
+    pub fn transition(&self, action: CampaignAction) -> Option<CampaignStatus> {
+        use CampaignAction as A;
+        use CampaignStatus as S;
+
+        match (self, action) {
+            (S::Draft | S::Scheduled, A::Schedule) => Some(S::Scheduled),
+            (S::Draft | S::Scheduled, A::Send) => Some(S::Sending),
+            (S::Sending, A::Pause) => Some(S::Paused),
+            (S::Paused, A::Resume) => Some(S::Sending),
+            (S::Scheduled | S::Sending | S::Paused, A::Cancel) => Some(S::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Actions allowed from this status.
+    pub fn allowed_actions(&self) -> Vec<CampaignAction> {
+        CampaignAction::ALL
+            .into_iter()
+            .filter(|a| self.transition(*a).is_some())
+            .collect()
+    }
+}
+
+impl fmt::Display for CampaignStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A lifecycle operation on a campaign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CampaignAction {
+    Schedule,
+    Send,
+    Pause,
+    Resume,
+    Cancel,
+}
+
+impl CampaignAction {
+    pub const ALL: [CampaignAction; 5] = [
+        CampaignAction::Schedule,
+        CampaignAction::Send,
+        CampaignAction::Pause,
+        CampaignAction::Resume,
+        CampaignAction::Cancel,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CampaignAction::Schedule => "schedule",
+            CampaignAction::Send => "send",
+            CampaignAction::Pause => "pause",
+            CampaignAction::Resume => "resume",
+            CampaignAction::Cancel => "cancel",
+        }
+    }
+}
+
+impl fmt::Display for CampaignAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An email campaign.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Campaign {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub status: CampaignStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Delivery counts so far.
+    #[serde(default, alias = "analytics")]
+    pub stats: CampaignStats,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_test: Option<SplitTest>,
+}
+
+impl Campaign {
+    /// Whether `action` is allowed from the current status.
+    pub fn can(&self, action: CampaignAction) -> bool {
+        self.check(action).is_ok()
+    }
+
+    /// The status `action` would lead to, or
+    /// [`VeilMailError::InvalidTransition`] if it is not allowed.
+    pub fn check(&self, action: CampaignAction) -> Result<CampaignStatus> {
+        if self.status == CampaignStatus::Unknown {
+            return Ok(CampaignStatus::Unknown);
+        }
+        self.status
+            .transition(action)
+            .ok_or_else(|| VeilMailError::InvalidTransition {
+                campaign_id: self.id.clone(),
+                status: self.status,
+                action,
+                allowed: self.status.allowed_actions(),
+            })
+    }
+}
+
+/// Delivery counts for a campaign.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CampaignStats {
+    #[serde(alias = "recipientCount", alias = "totalRecipients")]
+    pub recipients: u64,
+    pub queued: u64,
+    pub sent: u64,
+    pub delivered: u64,
+    pub bounced: u64,
+    pub opened: u64,
+    pub clicked: u64,
+}
+
+/// One observation from [`Campaigns::watch`].
+#[derive(Debug, Clone)]
+pub struct CampaignProgress {
+    pub campaign: Campaign,
+    /// Status at the previous observation, `None` for the first.
+    pub previous_status: Option<CampaignStatus>,
+    /// Time since watching started.
+    pub elapsed: Duration,
+}
+
+impl CampaignProgress {
+    pub fn status(&self) -> CampaignStatus {
+        self.campaign.status
+    }
+
+    pub fn stats(&self) -> &CampaignStats {
+        &self.campaign.stats
+    }
+
+    /// Whether the status differs from the previous observation. Always true
+    /// for the first.
+    pub fn status_changed(&self) -> bool {
+        self.previous_status != Some(self.campaign.status)
+    }
+}
+
+/// Fields for [`Campaigns::create`] and [`Campaigns::update`].
+///
+/// Fields without a setter can be passed with [`param`](Self::param).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_test: Option<SplitTest>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl CampaignParams {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn from(mut self, from: impl Into<String>) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+
+    pub fn audience_id(mut self, id: impl Into<String>) -> Self {
+        self.audience_id = Some(id.into());
+        self
+    }
+
+    pub fn template_id(mut self, id: impl Into<String>) -> Self {
+        self.template_id = Some(id.into());
+        self
+    }
+
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
+
+    pub fn split_test(mut self, test: SplitTest) -> Self {
+        self.split_test = Some(test);
+        self
+    }
+
+    /// Any other field, by its JSON name.
+    pub fn param(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// What decides the winner of a [`SplitTest`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinningMetric {
+    #[default]
+    Opens,
+    Clicks,
+    #[serde(other)]
+    Unknown,
+}
+
+impl WinningMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WinningMetric::Opens => "opens",
+            WinningMetric::Clicks => "clicks",
+            WinningMetric::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for WinningMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One version of a campaign in a [`SplitTest`]. Fields left unset use the
+/// campaign's own.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitVariant {
+    /// Assigned by the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// Share of the test group, in percent. Set it on every variant, adding
+    /// up to 100, or on none for an even split.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u8>,
+}
+
+impl SplitVariant {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn from(mut self, from: impl Into<String>) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
+
+    pub fn template_id(mut self, id: impl Into<String>) -> Self {
+        self.template_id = Some(id.into());
+        self
+    }
+
+    pub fn weight(mut self, percent: u8) -> Self {
+        self.weight = Some(percent);
+        self
+    }
+
+    fn overrides_anything(&self) -> bool {
+        self.subject.is_some()
+            || self.from.is_some()
+            || self.html.is_some()
+            || self.template_id.is_some()
+    }
+}
+
+/// An A/B test: `test_percentage` of the audience is split between the
+/// variants, evenly or by their weights, and after `evaluation_window_hours`
+/// the variant with the best `winning_metric` is sent to the rest.
+///
+/// # Example
+///
+/// ```
+/// use veilmail::resources::campaigns::{CampaignParams, SplitTest, SplitVariant, WinningMetric};
+///
+/// let test = SplitTest::new(WinningMetric::Opens)
+///     .variant(SplitVariant::new("A").subject("Spring sale starts today"))
+///     .variant(SplitVariant::new("B").subject("20% off everything this week"))
+///     .test_percentage(20)
+///     .evaluation_window_hours(4);
+/// assert!(test.validate().is_ok());
+///
+/// let params = CampaignParams::new("Spring sale")
+///     .audience_id("audience_xxxxx")
+///     .split_test(test);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitTest {
+    pub variants: Vec<SplitVariant>,
+    /// Share of the audience, 1 to 100, that receives a test variant.
+    pub test_percentage: u8,
+    #[serde(default)]
+    pub winning_metric: WinningMetric,
+    pub evaluation_window_hours: u32,
+}
+
+impl SplitTest {
+    /// A test with no variants, 10% test share, and a 4 hour window.
+    pub fn new(metric: WinningMetric) -> Self {
+        Self {
+            variants: Vec::new(),
+            test_percentage: 10,
+            winning_metric: metric,
+            evaluation_window_hours: 4,
+        }
+    }
+
+    pub fn variant(mut self, variant: SplitVariant) -> Self {
+        self.variants.push(variant);
+        self
+    }
+
+    pub fn test_percentage(mut self, percentage: u8) -> Self {
+        self.test_percentage = percentage;
+        self
+    }
+
+    pub fn evaluation_window_hours(mut self, hours: u32) -> Self {
+        self.evaluation_window_hours = hours;
+        self
+    }
+
+    /// Check the test locally. [`Campaigns::create`] and
+    /// [`Campaigns::update`] call this before sending.
+    ///
+    /// Requires at least two uniquely named variants that each override
+    /// something, weights on all variants adding up to 100 or on none, a
+    /// test percentage from 1 to 100, a non-zero window, and a known metric.
+    pub fn validate(&self) -> Result<()> {
+        let fail = |field: &str, message: String| {
+            Err(VeilMailError::invalid_input(
+                format!("splitTest.{}", field),
+                message,
+            ))
+        };
+
+        if self.variants.len() < 2 {
+            return fail(
+                "variants",
+                format!(
+                    "a split test needs at least 2 variants, got {}",
+                    self.variants.len()
+                ),
+            );
+        }
+        let mut names = HashSet::new();
+        for variant in &self.variants {
+            if variant.name.trim().is_empty() {
+                return fail("variants", "every variant needs a name".to_string());
+            }
+            if !names.insert(variant.name.as_str()) {
+                return fail(
+                    "variants",
+                    format!("duplicate variant name '{}'", variant.name),
+                );
+            }
+            if !variant.overrides_anything() {
+                return fail(
+                    "variants",
+                    format!(
+                        "variant '{}' does not change the subject, sender, or content",
+                        variant.name
+                    ),
+                );
+            }
+            if variant.weight == Some(0) {
+                return fail(
+                    "variants",
+                    format!("variant '{}' has a weight of 0", variant.name),
+                );
+            }
+        }
+        let weighted = self.variants.iter().filter(|v| v.weight.is_some()).count();
+        if weighted > 0 {
+            if weighted < self.variants.len() {
+                return fail(
+                    "variants",
+                    "set a weight on every variant or on none".to_string(),
+                );
+            }
+            let total: u32 = self
+                .variants
+                .iter()
+                .filter_map(|v| v.weight)
+                .map(u32::from)
+                .sum();
+            if total != 100 {
+                return fail(
+                    "variants",
+                    format!("variant weights must add up to 100, got {}", total),
+                );
+            }
+        }
+        if !(1..=100).contains(&self.test_percentage) {
+            return fail(
+                "testPercentage",
+                format!(
+                    "test percentage must be between 1 and 100, got {}",
+                    self.test_percentage
+                ),
+            );
+        }
+        if self.evaluation_window_hours == 0 {
+            return fail(
+                "evaluationWindowHours",
+                "evaluation window must be at least 1 hour".to_string(),
+            );
+        }
+        if self.winning_metric == WinningMetric::Unknown {
+            return fail(
+                "winningMetric",
+                "winning metric must be opens or clicks".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A campaign argument for lifecycle methods.
+///
+/// The transition is checked against the campaign's status before the
+/// action is sent, failing with [`VeilMailError::InvalidTransition`]. Passing
+/// a [`Campaign`] checks against its status as fetched; passing an ID fetches
+/// the campaign first.
+///
+/// ```no_run
+/// # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+/// use veilmail::error::VeilMailError;
+///
+/// let campaign = client.campaigns().get("campaign_xxxxx").await?;
+/// match client.campaigns().pause(&campaign).await {
+///     Ok(paused) => println!("now {}", paused.status),
+///     // e.g. "Cannot pause campaign campaign_xxxxx while it is draft (allowed: schedule, send)"
+///     Err(e @ VeilMailError::InvalidTransition { .. }) => eprintln!("{}", e),
+///     Err(e) => return Err(e),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait CampaignRef {
+    fn campaign_id(&self) -> &str;
+
+    /// The campaign as last fetched, if available, for local validation.
+    /// When `None`, the campaign is fetched before each lifecycle action.
+    fn as_campaign(&self) -> Option<&Campaign> {
+        None
+    }
+}
+
+impl CampaignRef for str {
+    fn campaign_id(&self) -> &str {
+        self
+    }
+}
+
+impl CampaignRef for String {
+    fn campaign_id(&self) -> &str {
+        self
+    }
+}
+
+impl CampaignRef for Campaign {
+    fn campaign_id(&self) -> &str {
+        &self.id
+    }
+
+    fn as_campaign(&self) -> Option<&Campaign> {
+        Some(self)
+    }
+}
 
 /// Campaign management.
 pub struct Campaigns<'a> {
@@ -9,75 +588,179 @@ pub struct Campaigns<'a> {
 }
 
 impl<'a> Campaigns<'a> {
-    pub async fn create(&self, params: Value) -> Result<Value> {
+    /// Create a campaign from a [`CampaignParams`] or any JSON body. A split
+    /// test in the body is validated locally first.
+    pub async fn create(&self, params: impl Serialize) -> Result<Campaign> {
+        let params = validated(params)?;
         let response = self.http.post("/v1/campaigns", Some(&params)).await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http.get("/v1/campaigns", params).await
+    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Page<Campaign>> {
+        let response = self.http.get("/v1/campaigns", params).await?;
+        Ok(serde_json::from_value(response)?)
     }
 
-    pub async fn get(&self, id: &str) -> Result<Value> {
+    pub async fn get(&self, id: &str) -> Result<Campaign> {
         let response = self
             .http
             .get(&format!("/v1/campaigns/{}", id), None)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn update(&self, id: &str, params: Value) -> Result<Value> {
+    /// Update a campaign. A split test in the body is validated locally first.
+    pub async fn update(&self, id: &str, params: impl Serialize) -> Result<Campaign> {
+        let params = validated(params)?;
         let response = self
             .http
             .patch(&format!("/v1/campaigns/{}", id), &params)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn delete(&self, id: &str) -> Result<()> {
         self.http.delete(&format!("/v1/campaigns/{}", id)).await
     }
 
-    pub async fn schedule(&self, id: &str, params: Value) -> Result<Value> {
-        let response = self
-            .http
-            .post(&format!("/v1/campaigns/{}/schedule", id), Some(&params))
-            .await?;
-        Ok(unwrap_data(response))
+    /// Schedule (or reschedule) a draft or scheduled campaign.
+    pub async fn schedule<C>(&self, campaign: &C, params: Value) -> Result<Campaign>
+    where
+        C: CampaignRef + ?Sized,
+    {
+        self.lifecycle(campaign, CampaignAction::Schedule, Some(&params))
+            .await
     }
 
-    pub async fn send(&self, id: &str) -> Result<Value> {
-        let response = self
-            .http
-            .post(&format!("/v1/campaigns/{}/send", id), None)
-            .await?;
-        Ok(unwrap_data(response))
+    /// Schedule a campaign with a typed send time, checking that it is in
+    /// the future first.
+    ///
+    /// Accepts a [`SendAt`], a [`SystemTime`](std::time::SystemTime), or,
+    /// with the `chrono` / `time` features, a `chrono::DateTime<Tz>` /
+    /// `time::OffsetDateTime`.
+    pub async fn schedule_at<C>(&self, campaign: &C, at: impl Into<SendAt>) -> Result<Campaign>
+    where
+        C: CampaignRef + ?Sized,
+    {
+        let at = at.into();
+        at.validate(std::time::SystemTime::now())?;
+        self.schedule(campaign, at.to_params()?).await
     }
 
-    pub async fn pause(&self, id: &str) -> Result<Value> {
-        let response = self
-            .http
-            .post(&format!("/v1/campaigns/{}/pause", id), None)
-            .await?;
-        Ok(unwrap_data(response))
+    /// Send a draft or scheduled campaign now.
+    pub async fn send<C: CampaignRef + ?Sized>(&self, campaign: &C) -> Result<Campaign> {
+        self.lifecycle(campaign, CampaignAction::Send, None).await
     }
 
-    pub async fn resume(&self, id: &str) -> Result<Value> {
-        let response = self
-            .http
-            .post(&format!("/v1/campaigns/{}/resume", id), None)
-            .await?;
-        Ok(unwrap_data(response))
+    /// Pause a sending campaign.
+    pub async fn pause<C: CampaignRef + ?Sized>(&self, campaign: &C) -> Result<Campaign> {
+        self.lifecycle(campaign, CampaignAction::Pause, None).await
     }
 
-    pub async fn cancel(&self, id: &str) -> Result<Value> {
-        let response = self
-            .http
-            .post(&format!("/v1/campaigns/{}/cancel", id), None)
-            .await?;
-        Ok(unwrap_data(response))
+    /// Resume a paused campaign.
+    pub async fn resume<C: CampaignRef + ?Sized>(&self, campaign: &C) -> Result<Campaign> {
+        self.lifecycle(campaign, CampaignAction::Resume, None).await
+    }
+
+    /// Cancel a scheduled, sending, or paused campaign.
+    pub async fn cancel<C: CampaignRef + ?Sized>(&self, campaign: &C) -> Result<Campaign> {
+        self.lifecycle(campaign, CampaignAction::Cancel, None).await
+    }
+
+    /// Poll a campaign, yielding a [`CampaignProgress`] whenever its status
+    /// or counts change.
+    ///
+    /// The first poll is always yielded. The stream ends after yielding a
+    /// terminal status (`sent` or `cancelled`), and fails with
+    /// [`VeilMailError::CampaignTimeout`] once `policy.timeout` elapses. Use
+    /// `policy.multiplier(1.0)` for a fixed poll interval.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+    /// use futures_util::TryStreamExt;
+    /// use std::time::Duration;
+    /// use veilmail::polling::PollPolicy;
+    ///
+    /// let policy = PollPolicy::new(Duration::from_secs(2 * 60 * 60))
+    ///     .initial_interval(Duration::from_secs(10))
+    ///     .multiplier(1.0);
+    /// let mut progress = Box::pin(client.campaigns().watch("campaign_xxxxx", policy));
+    /// while let Some(p) = progress.try_next().await? {
+    ///     let stats = p.stats();
+    ///     println!("{}: {}/{} sent, {} bounced", p.status(), stats.sent, stats.recipients, stats.bounced);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch(
+        &self,
+        id: &str,
+        policy: PollPolicy,
+    ) -> impl Stream<Item = Result<CampaignProgress>> + Send + 'a {
+        let http = self.http;
+        let id = id.to_string();
+        let state = WatchState {
+            started: Instant::now(),
+            interval: policy.initial_interval,
+            previous: None,
+        };
+
+        stream::try_unfold(Some(state), move |state| {
+            let id = id.clone();
+            async move {
+                let Some(mut state) = state else {
+                    return Ok::<_, VeilMailError>(None);
+                };
+
+                loop {
+                    if let Some(previous) = &state.previous {
+                        let elapsed = state.started.elapsed();
+                        if elapsed >= policy.timeout {
+                            return Err(VeilMailError::CampaignTimeout {
+                                campaign_id: id,
+                                elapsed,
+                                status: previous.status,
+                            });
+                        }
+                        tokio::time::sleep(state.interval.min(policy.timeout - elapsed)).await;
+                        state.interval = policy.next_interval(state.interval);
+                    }
+
+                    let campaign = Campaigns { http }.get(&id).await?;
+                    let previous_status = state.previous.as_ref().map(|c| c.status);
+                    let changed = state.previous.as_ref().is_none_or(|previous| {
+                        previous.status != campaign.status || previous.stats != campaign.stats
+                    });
+                    state.previous = Some(campaign.clone());
+
+                    if changed {
+                        let finished = campaign.status.is_terminal();
+                        let progress = CampaignProgress {
+                            campaign,
+                            previous_status,
+                            elapsed: state.started.elapsed(),
+                        };
+                        return Ok(Some((progress, (!finished).then_some(state))));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Poll until the campaign is `sent` or `cancelled`, returning it.
+    ///
+    /// Fails with [`VeilMailError::CampaignTimeout`] if it is still going
+    /// when `policy.timeout` elapses.
+    pub async fn wait_until_finished(&self, id: &str, policy: PollPolicy) -> Result<Campaign> {
+        let mut progress = Box::pin(self.watch(id, policy));
+        let mut last = None;
+        while let Some(p) = progress.try_next().await? {
+            last = Some(p.campaign);
+        }
+        last.ok_or_else(|| VeilMailError::Other(format!("campaign {} was never fetched", id)))
     }
-}
 
     pub async fn send_test(&self, id: &str, params: Value) -> Result<Value> {
         self.http
@@ -92,16 +775,207 @@ impl<'a> Campaigns<'a> {
             .await
     }
 
-    pub async fn links(&self, id: &str, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http
+    pub async fn links(&self, id: &str, params: Option<&[(&str, &str)]>) -> Result<LinkReports> {
+        let response = self
+            .http
             .get(&format!("/v1/campaigns/{}/links", id), params)
-            .await
+            .await?;
+        LinkReports::from_response(response)
+    }
+
+    async fn lifecycle<C: CampaignRef + ?Sized>(
+        &self,
+        campaign: &C,
+        action: CampaignAction,
+        body: Option<&Value>,
+    ) -> Result<Campaign> {
+        match campaign.as_campaign() {
+            Some(known) => known.check(action)?,
+            None => self.get(campaign.campaign_id()).await?.check(action)?,
+        };
+        let response = self
+            .http
+            .post(
+                &format!("/v1/campaigns/{}/{}", campaign.campaign_id(), action),
+                body,
+            )
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 }
 
+/// Serialize a create/update body, validating any split test in it.
+fn validated(params: impl Serialize) -> Result<Value> {
+    let params = serde_json::to_value(params)?;
+    if let Some(test) = params.get("splitTest").filter(|t| !t.is_null()) {
+        let test: SplitTest = serde_json::from_value(test.clone())
+            .map_err(|e| VeilMailError::invalid_input("splitTest", e.to_string()))?;
+        test.validate()?;
+    }
+    Ok(params)
+}
+
+struct WatchState {
+    started: Instant,
+    interval: Duration,
+    previous: Option<Campaign>,
+}
+
 fn unwrap_data(value: Value) -> Value {
     match value.get("data") {
         Some(data) if data.is_object() => data.clone(),
         _ => value,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use CampaignAction as A;
+    use CampaignStatus as S;
+
+    fn campaign(status: &str) -> Campaign {
+        serde_json::from_value(json!({ "id": "campaign_1", "status": status })).unwrap()
+    }
+
+    #[test]
+    fn transition_table() {
+        let table = [
+            (S::Draft, A::Schedule, Some(S::Scheduled)),
+            (S::Draft, A::Send, Some(S::Sending)),
+            (S::Scheduled, A::Schedule, Some(S::Scheduled)),
+            (S::Scheduled, A::Send, Some(S::Sending)),
+            (S::Scheduled, A::Cancel, Some(S::Cancelled)),
+            (S::Sending, A::Pause, Some(S::Paused)),
+            (S::Sending, A::Cancel, Some(S::Cancelled)),
+            (S::Paused, A::Resume, Some(S::Sending)),
+            (S::Paused, A::Cancel, Some(S::Cancelled)),
+        ];
+        let statuses = [
+            S::Draft,
+            S::Scheduled,
+            S::Sending,
+            S::Paused,
+            S::Sent,
+            S::Cancelled,
+            S::Unknown,
+        ];
+        for status in statuses {
+            for action in A::ALL {
+                let expected = table
+                    .iter()
+                    .find(|(s, a, _)| *s == status && *a == action)
+                    .and_then(|(_, _, to)| *to);
+                assert_eq!(
+                    status.transition(action),
+                    expected,
+                    "{} from {}",
+                    action,
+                    status
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn allowed_actions() {
+        assert_eq!(S::Draft.allowed_actions(), [A::Schedule, A::Send]);
+        assert_eq!(S::Sending.allowed_actions(), [A::Pause, A::Cancel]);
+        assert_eq!(S::Paused.allowed_actions(), [A::Resume, A::Cancel]);
+        for status in [S::Sent, S::Cancelled] {
+            assert!(status.is_terminal());
+            assert!(status.allowed_actions().is_empty());
+        }
+    }
+
+    #[test]
+    fn check_reports_invalid_transition() {
+        match campaign("draft").check(A::Resume) {
+            Err(VeilMailError::InvalidTransition {
+                campaign_id,
+                status,
+                action,
+                allowed,
+            }) => {
+                assert_eq!(campaign_id, "campaign_1");
+                assert_eq!(status, S::Draft);
+                assert_eq!(action, A::Resume);
+                assert_eq!(allowed, [A::Schedule, A::Send]);
+            }
+            other => panic!("expected InvalidTransition, got {:?}", other),
+        }
+        assert_eq!(campaign("paused").check(A::Resume).unwrap(), S::Sending);
+        assert!(campaign("sending").can(A::Pause));
+        assert!(!campaign("sent").can(A::Cancel));
+    }
+
+    #[test]
+    fn unknown_status_is_not_checked_locally() {
+        let campaign = campaign("archived");
+        assert_eq!(campaign.status, S::Unknown);
+        assert!(A::ALL.into_iter().all(|a| campaign.can(a)));
+    }
+
+    fn split_test() -> SplitTest {
+        SplitTest::new(WinningMetric::Opens)
+            .variant(SplitVariant::new("A").subject("one"))
+            .variant(SplitVariant::new("B").subject("two"))
+    }
+
+    fn validation_message(test: &SplitTest) -> String {
+        match test.validate() {
+            Err(VeilMailError::InvalidInput { field, message }) => {
+                assert!(field.starts_with("splitTest."), "{}", field);
+                message
+            }
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn split_test_needs_two_distinct_variants() {
+        assert!(split_test().validate().is_ok());
+
+        let one = SplitTest::new(WinningMetric::Opens).variant(SplitVariant::new("A").subject("x"));
+        assert!(validation_message(&one).contains("at least 2 variants, got 1"));
+
+        let duplicate = split_test().variant(SplitVariant::new("A").subject("three"));
+        assert!(validation_message(&duplicate).contains("duplicate variant name 'A'"));
+
+        let unnamed = split_test().variant(SplitVariant::new(" ").subject("three"));
+        assert!(validation_message(&unnamed).contains("needs a name"));
+
+        let unchanged = split_test().variant(SplitVariant::new("C"));
+        assert!(validation_message(&unchanged).contains("does not change"));
+    }
+
+    #[test]
+    fn split_test_weights_must_add_up_to_100() {
+        let weighted = |a, b| {
+            SplitTest::new(WinningMetric::Clicks)
+                .variant(SplitVariant::new("A").subject("one").weight(a))
+                .variant(SplitVariant::new("B").subject("two").weight(b))
+        };
+        assert!(weighted(70, 30).validate().is_ok());
+        assert!(validation_message(&weighted(70, 20)).contains("add up to 100, got 90"));
+        assert!(validation_message(&weighted(100, 0)).contains("weight of 0"));
+
+        let partial = split_test().variant(SplitVariant::new("C").subject("three").weight(100));
+        assert!(validation_message(&partial).contains("every variant or on none"));
+    }
+
+    #[test]
+    fn split_test_winner_criteria() {
+        assert!(split_test().test_percentage(100).validate().is_ok());
+        assert!(validation_message(&split_test().test_percentage(0)).contains("between 1 and 100"));
+        assert!(validation_message(&split_test().test_percentage(101)).contains("got 101"));
+        assert!(validation_message(&split_test().evaluation_window_hours(0))
+            .contains("at least 1 hour"));
+
+        let mut unknown = split_test();
+        unknown.winning_metric = WinningMetric::Unknown;
+        assert!(validation_message(&unknown).contains("opens or clicks"));
+    }
+}
@@ -1,7 +1,186 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt;
+use std::time::Instant;
 
-use crate::error::Result;
+use crate::error::{Result, VeilMailError};
 use crate::http::HttpClient;
+use crate::polling::PollPolicy;
+
+/// Verification status of a domain or one of its DNS records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainStatus {
+    #[default]
+    Pending,
+    Verified,
+    Failed,
+    TemporaryFailure,
+    /// A status this SDK does not know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+impl DomainStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainStatus::Pending => "pending",
+            DomainStatus::Verified => "verified",
+            DomainStatus::Failed => "failed",
+            DomainStatus::TemporaryFailure => "temporary_failure",
+            DomainStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for DomainStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// DNS record type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DnsRecordKind {
+    #[serde(alias = "txt")]
+    Txt,
+    #[serde(alias = "cname")]
+    Cname,
+    #[serde(alias = "mx")]
+    Mx,
+    /// A record type this SDK does not know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+impl DnsRecordKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DnsRecordKind::Txt => "TXT",
+            DnsRecordKind::Cname => "CNAME",
+            DnsRecordKind::Mx => "MX",
+            DnsRecordKind::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for DnsRecordKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a required DNS record is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordPurpose {
+    Spf,
+    Dkim,
+    Dmarc,
+    /// Bounce handling for the return-path (envelope sender) domain.
+    ReturnPath,
+    /// Domain ownership token.
+    Verification,
+}
+
+impl RecordPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordPurpose::Spf => "spf",
+            RecordPurpose::Dkim => "dkim",
+            RecordPurpose::Dmarc => "dmarc",
+            RecordPurpose::ReturnPath => "return_path",
+            RecordPurpose::Verification => "verification",
+        }
+    }
+}
+
+impl fmt::Display for RecordPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A DNS record that must be published for a domain to verify.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsRecord {
+    #[serde(rename = "type")]
+    pub kind: DnsRecordKind,
+    /// Fully qualified record name.
+    pub name: String,
+    pub value: String,
+    /// MX priority.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u16>,
+    #[serde(default)]
+    pub status: DomainStatus,
+}
+
+impl DnsRecord {
+    /// Whether this record has been found and matches.
+    pub fn is_verified(&self) -> bool {
+        self.status == DomainStatus::Verified
+    }
+
+    /// Classify the record from its type, name, and value.
+    pub fn purpose(&self) -> RecordPurpose {
+        let name = self.name.to_ascii_lowercase();
+        let value = self.value.trim_matches('"').to_ascii_lowercase();
+
+        if name.starts_with("_dmarc.") || value.starts_with("v=dmarc1") {
+            RecordPurpose::Dmarc
+        } else if name.contains("._domainkey.") || value.starts_with("v=dkim1") {
+            RecordPurpose::Dkim
+        } else if value.starts_with("v=spf1") {
+            RecordPurpose::Spf
+        } else if self.kind == DnsRecordKind::Txt {
+            RecordPurpose::Verification
+        } else {
+            RecordPurpose::ReturnPath
+        }
+    }
+}
+
+/// A sending domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Domain {
+    pub id: String,
+    /// The domain name, e.g. `mail.example.com`.
+    #[serde(alias = "domain")]
+    pub name: String,
+    #[serde(default)]
+    pub status: DomainStatus,
+    /// Records to publish, with their individual verification status.
+    #[serde(default, alias = "dnsRecords")]
+    pub records: Vec<DnsRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<String>,
+}
+
+impl Domain {
+    /// Whether the domain is verified and ready to send from.
+    pub fn is_verified(&self) -> bool {
+        self.status == DomainStatus::Verified
+    }
+
+    /// Records that are not yet verified.
+    pub fn missing_records(&self) -> impl Iterator<Item = &DnsRecord> {
+        self.records.iter().filter(|r| !r.is_verified())
+    }
+}
+
+/// A DNS record whose verification status changed while waiting for a
+/// domain to verify.
+#[derive(Debug, Clone)]
+pub struct RecordStatusChange {
+    pub record: DnsRecord,
+    /// Status at the previous check, or `None` on the first check.
+    pub previous: Option<DomainStatus>,
+}
 
 /// Domain management for email sending.
 pub struct Domains<'a> {
@@ -9,18 +188,18 @@ pub struct Domains<'a> {
 }
 
 impl<'a> Domains<'a> {
-    pub async fn create(&self, params: Value) -> Result<Value> {
+    pub async fn create(&self, params: Value) -> Result<Domain> {
         let response = self.http.post("/v1/domains", Some(&params)).await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
         self.http.get("/v1/domains", params).await
     }
 
-    pub async fn get(&self, id: &str) -> Result<Value> {
+    pub async fn get(&self, id: &str) -> Result<Domain> {
         let response = self.http.get(&format!("/v1/domains/{}", id), None).await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn update(&self, id: &str, params: Value) -> Result<Value> {
@@ -29,17 +208,102 @@ impl<'a> Domains<'a> {
             .await
     }
 
-    pub async fn verify(&self, id: &str) -> Result<Value> {
+    pub async fn verify(&self, id: &str) -> Result<Domain> {
         let response = self
             .http
             .post(&format!("/v1/domains/{}/verify", id), None)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn delete(&self, id: &str) -> Result<()> {
         self.http.delete(&format!("/v1/domains/{}", id)).await
     }
+
+    /// Call [`verify`](Self::verify) repeatedly until the domain is verified.
+    ///
+    /// Returns [`VeilMailError::VerificationTimeout`] listing the records that
+    /// are still not verified when `policy.timeout` elapses, or
+    /// [`VeilMailError::VerificationFailed`] as soon as the domain is marked
+    /// [`DomainStatus::Failed`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+    /// use veilmail::polling::PollPolicy;
+    ///
+    /// let domain = client
+    ///     .domains()
+    ///     .wait_until_verified("domain_xxxxx", PollPolicy::default())
+    ///     .await?;
+    /// println!("{} is verified", domain.name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_until_verified(&self, id: &str, policy: PollPolicy) -> Result<Domain> {
+        self.wait_until_verified_with_progress(id, policy, |_| {})
+            .await
+    }
+
+    /// Like [`wait_until_verified`](Self::wait_until_verified), calling
+    /// `on_change` whenever a DNS record's status changes.
+    ///
+    /// Every record is reported on the first check.
+    pub async fn wait_until_verified_with_progress<F>(
+        &self,
+        id: &str,
+        policy: PollPolicy,
+        mut on_change: F,
+    ) -> Result<Domain>
+    where
+        F: FnMut(&RecordStatusChange),
+    {
+        let started = Instant::now();
+        let mut interval = policy.initial_interval;
+        let mut previous: Option<Vec<DnsRecord>> = None;
+
+        loop {
+            let domain = self.verify(id).await?;
+
+            for record in &domain.records {
+                let before = previous.as_ref().and_then(|records| {
+                    records
+                        .iter()
+                        .find(|r| r.kind == record.kind && r.name == record.name)
+                        .map(|r| r.status)
+                });
+                if before != Some(record.status) {
+                    on_change(&RecordStatusChange {
+                        record: record.clone(),
+                        previous: before,
+                    });
+                }
+            }
+
+            if domain.is_verified() {
+                return Ok(domain);
+            }
+            if domain.status == DomainStatus::Failed {
+                return Err(VeilMailError::VerificationFailed {
+                    domain: Box::new(domain),
+                });
+            }
+
+            let elapsed = started.elapsed();
+            if elapsed >= policy.timeout {
+                return Err(VeilMailError::VerificationTimeout {
+                    domain_id: domain.id.clone(),
+                    elapsed,
+                    failing_records: domain.missing_records().cloned().collect(),
+                });
+            }
+
+            tokio::time::sleep(interval.min(policy.timeout - elapsed)).await;
+            interval = policy.next_interval(interval);
+            previous = Some(domain.records);
+        }
+    }
 }
 
 fn unwrap_data(value: Value) -> Value {
@@ -1,7 +1,10 @@
 use serde_json::{json, Value};
 
-use crate::error::Result;
+use crate::error::{Result, VeilMailError};
 use crate::http::HttpClient;
+use crate::json::value_kind;
+use crate::reports::links::LinkReports;
+use crate::resources::templates::VeilTemplate;
 
 /// Email sending and management.
 pub struct Emails<'a> {
@@ -14,6 +17,42 @@ impl<'a> Emails<'a> {
         self.http.post("/v1/emails", Some(&params)).await
     }
 
+    /// Send a stored template with typed data.
+    ///
+    /// `params` holds the other fields of the email, such as `from` and `to`;
+    /// `templateId` and `templateData` are filled in from `data`. Fails with
+    /// [`VeilMailError::InvalidInput`] if `params` is not a JSON object.
+    ///
+    /// ```no_run
+    /// # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+    /// # #[derive(serde::Serialize)]
+    /// # struct WelcomeEmail { name: String }
+    /// # impl veilmail::VeilTemplate for WelcomeEmail {
+    /// #     const TEMPLATE_ID: &'static str = "tmpl_welcome";
+    /// #     const VARIABLES: &'static [&'static str] = &["name"];
+    /// # }
+    /// use serde_json::json;
+    ///
+    /// client
+    ///     .emails()
+    ///     .send_template(
+    ///         &WelcomeEmail { name: "Alice".into() },
+    ///         json!({ "from": "hello@yourdomain.com", "to": ["alice@example.com"] }),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_template<T: VeilTemplate>(&self, data: &T, params: Value) -> Result<Value> {
+        let mut body = match params {
+            Value::Object(map) => map,
+            other => return Err(invalid_params(value_kind(&other))),
+        };
+        body.insert("templateId".into(), json!(T::TEMPLATE_ID));
+        body.insert("templateData".into(), serde_json::to_value(data)?);
+        self.send(Value::Object(body)).await
+    }
+
     /// Send a batch of up to 100 emails.
     pub async fn send_batch(&self, emails: Vec<Value>) -> Result<Value> {
         let body = json!({ "emails": emails });
@@ -45,9 +84,15 @@ impl<'a> Emails<'a> {
     }
 
     /// Get tracked link analytics for a specific email.
-    pub async fn links(&self, id: &str, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http
+    pub async fn links(&self, id: &str, params: Option<&[(&str, &str)]>) -> Result<LinkReports> {
+        let response = self
+            .http
             .get(&format!("/v1/emails/{}/links", id), params)
-            .await
+            .await?;
+        LinkReports::from_response(response)
     }
 }
+
+fn invalid_params(kind: &str) -> VeilMailError {
+    VeilMailError::invalid_input("params", format!("must be a JSON object, not {}", kind))
+}
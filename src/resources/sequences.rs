@@ -1,7 +1,465 @@
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::time::Duration;
 
-use crate::error::Result;
+use crate::error::{Result, VeilMailError};
 use crate::http::HttpClient;
+use crate::pagination::Page;
+
+/// The longest delay a single step may hold.
+pub const MAX_DELAY: Duration = Duration::from_secs(365 * 86_400);
+
+/// Lifecycle status of a sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceStatus {
+    #[default]
+    Draft,
+    Active,
+    Paused,
+    Archived,
+    #[serde(other)]
+    Unknown,
+}
+
+impl SequenceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SequenceStatus::Draft => "draft",
+            SequenceStatus::Active => "active",
+            SequenceStatus::Paused => "paused",
+            SequenceStatus::Archived => "archived",
+            SequenceStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for SequenceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What enrolls subscribers into a sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum Trigger {
+    /// Only through [`Sequences::enroll`].
+    Manual,
+    /// A subscriber joins an audience.
+    SubscriberAdded { audience_id: String },
+    /// A subscriber opts in to a topic.
+    TopicSubscribed { topic_id: String },
+    /// A custom event is tracked for a subscriber.
+    Event { name: String },
+    #[serde(other)]
+    Unknown,
+}
+
+/// Unit of a delay step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelayUnit {
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+}
+
+impl DelayUnit {
+    fn seconds(&self) -> u64 {
+        match self {
+            DelayUnit::Minutes => 60,
+            DelayUnit::Hours => 3_600,
+            DelayUnit::Days => 86_400,
+            DelayUnit::Weeks => 7 * 86_400,
+        }
+    }
+}
+
+/// What a condition step checks. Subscribers who fail it leave the
+/// sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum Condition {
+    /// Opened the most recent email step.
+    OpenedPrevious,
+    /// Clicked a link in the most recent email step.
+    ClickedPrevious,
+    /// A subscriber property has this value.
+    PropertyEquals { property: String, value: Value },
+    #[serde(other)]
+    Unknown,
+}
+
+/// What a step does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum StepAction {
+    /// Send a stored template.
+    Email {
+        template_id: String,
+        /// Overrides the template's subject.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subject: Option<String>,
+    },
+    /// Wait before the next step.
+    Delay { amount: u32, unit: DelayUnit },
+    /// Continue only if `condition` holds.
+    Condition { condition: Condition },
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for DelayUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DelayUnit::Minutes => "minutes",
+            DelayUnit::Hours => "hours",
+            DelayUnit::Days => "days",
+            DelayUnit::Weeks => "weeks",
+        })
+    }
+}
+
+impl fmt::Display for StepAction {
+    /// A one-line summary, e.g. `email tmpl_welcome` or `delay 2 days`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepAction::Email {
+                template_id,
+                subject: None,
+            } => write!(f, "email {}", template_id),
+            StepAction::Email {
+                template_id,
+                subject: Some(subject),
+            } => write!(f, "email {} ({:?})", template_id, subject),
+            StepAction::Delay { amount, unit } => write!(f, "delay {} {}", amount, unit),
+            StepAction::Condition { condition } => match condition {
+                Condition::OpenedPrevious => f.write_str("if opened previous"),
+                Condition::ClickedPrevious => f.write_str("if clicked previous"),
+                Condition::PropertyEquals { property, value } => {
+                    write!(f, "if {} = {}", property, value)
+                }
+                Condition::Unknown => f.write_str("if <unknown condition>"),
+            },
+            StepAction::Unknown => f.write_str("<unknown step>"),
+        }
+    }
+}
+
+/// One step of a sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceStep {
+    /// Assigned by the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// 0-based position, assigned by the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<u32>,
+    #[serde(flatten)]
+    pub action: StepAction,
+}
+
+impl SequenceStep {
+    pub fn email(template_id: impl Into<String>) -> Self {
+        Self::new(StepAction::Email {
+            template_id: template_id.into(),
+            subject: None,
+        })
+    }
+
+    pub fn delay(amount: u32, unit: DelayUnit) -> Self {
+        Self::new(StepAction::Delay { amount, unit })
+    }
+
+    pub fn condition(condition: Condition) -> Self {
+        Self::new(StepAction::Condition { condition })
+    }
+
+    fn new(action: StepAction) -> Self {
+        Self {
+            id: None,
+            position: None,
+            action,
+        }
+    }
+
+    /// How long a delay step waits, `None` for other steps.
+    pub fn delay_duration(&self) -> Option<Duration> {
+        match &self.action {
+            StepAction::Delay { amount, unit } => {
+                Some(Duration::from_secs(u64::from(*amount) * unit.seconds()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check the step on its own: a template ID is set, a delay is between
+    /// one unit and [`MAX_DELAY`], and a property condition names a
+    /// property.
+    pub fn validate(&self) -> Result<()> {
+        self.validate_at("step")
+    }
+
+    fn validate_at(&self, field: &str) -> Result<()> {
+        match &self.action {
+            StepAction::Email { template_id, .. } if template_id.trim().is_empty() => Err(invalid(
+                format!("{}.templateId", field),
+                "email step has no template",
+            )),
+            StepAction::Delay { amount: 0, .. } => Err(invalid(
+                format!("{}.amount", field),
+                "delay must be at least 1",
+            )),
+            StepAction::Delay { .. } if self.delay_duration() > Some(MAX_DELAY) => Err(invalid(
+                format!("{}.amount", field),
+                "delay is longer than 365 days",
+            )),
+            StepAction::Condition {
+                condition: Condition::PropertyEquals { property, .. },
+            } if property.trim().is_empty() => Err(invalid(
+                format!("{}.condition.property", field),
+                "property condition has no property name",
+            )),
+            StepAction::Condition {
+                condition: Condition::Unknown,
+            }
+            | StepAction::Unknown => Err(invalid(field.to_string(), "unknown step type")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check a full list of steps, in order.
+///
+/// Besides [`SequenceStep::validate`] for each step, this requires at least
+/// one email, no two delays in a row, an email before any opened/clicked
+/// condition, and that the sequence does not end on a delay or condition.
+pub fn validate_steps(steps: &[SequenceStep]) -> Result<()> {
+    let mut seen_email = false;
+    let mut previous_delay = false;
+    for (i, step) in steps.iter().enumerate() {
+        let field = format!("steps[{}]", i);
+        step.validate_at(&field)?;
+        match &step.action {
+            StepAction::Email { .. } => seen_email = true,
+            StepAction::Delay { .. } if previous_delay => {
+                return Err(invalid(
+                    field,
+                    "two delays in a row; combine them into one step",
+                ))
+            }
+            StepAction::Condition {
+                condition: Condition::OpenedPrevious | Condition::ClickedPrevious,
+            } if !seen_email => {
+                return Err(invalid(
+                    field,
+                    "opened/clicked condition has no earlier email to check",
+                ))
+            }
+            _ => {}
+        }
+        previous_delay = matches!(step.action, StepAction::Delay { .. });
+    }
+
+    if !seen_email {
+        return Err(invalid("steps".to_string(), "sequence has no email step"));
+    }
+    match steps.last().map(|s| &s.action) {
+        Some(StepAction::Delay { .. }) => Err(invalid(
+            format!("steps[{}]", steps.len() - 1),
+            "sequence ends with a delay",
+        )),
+        Some(StepAction::Condition { .. }) => Err(invalid(
+            format!("steps[{}]", steps.len() - 1),
+            "sequence ends with a condition",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// An automation sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sequence {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub status: SequenceStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<Trigger>,
+    #[serde(default)]
+    pub steps: Vec<SequenceStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Where a subscriber is in a sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrollmentStatus {
+    /// Still receiving steps.
+    #[default]
+    Active,
+    /// Reached the end of the sequence.
+    Completed,
+    /// Left early: removed, failed a condition, or unsubscribed.
+    Exited,
+    #[serde(other)]
+    Unknown,
+}
+
+impl EnrollmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnrollmentStatus::Active => "active",
+            EnrollmentStatus::Completed => "completed",
+            EnrollmentStatus::Exited => "exited",
+            EnrollmentStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for EnrollmentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A subscriber's place in a sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Enrollment {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscriber_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub status: EnrollmentStatus,
+    /// Position of the step the subscriber is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_step: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrolled_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+}
+
+/// Body for [`Sequences::create`], built and validated by
+/// [`SequenceBuilder`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceParams {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<Trigger>,
+    pub steps: Vec<SequenceStep>,
+}
+
+/// Builds a sequence step by step, checking it with [`validate_steps`] on
+/// [`build`](Self::build).
+///
+/// # Example
+///
+/// ```
+/// use veilmail::resources::sequences::{Condition, SequenceBuilder, Trigger};
+///
+/// let onboarding = SequenceBuilder::new("Onboarding")
+///     .trigger(Trigger::SubscriberAdded { audience_id: "audience_xxxxx".into() })
+///     .email("tmpl_welcome")
+///     .delay_days(2)
+///     .condition(Condition::OpenedPrevious)
+///     .email("tmpl_getting_started")
+///     .build()
+///     .unwrap();
+/// assert_eq!(onboarding.steps.len(), 4);
+///
+/// // A delay with nothing after it is rejected locally.
+/// assert!(SequenceBuilder::new("Broken").email("tmpl_welcome").delay_hours(1).build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SequenceBuilder {
+    params: SequenceParams,
+}
+
+impl SequenceBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            params: SequenceParams {
+                name: name.into(),
+                trigger: None,
+                steps: Vec::new(),
+            },
+        }
+    }
+
+    pub fn trigger(mut self, trigger: Trigger) -> Self {
+        self.params.trigger = Some(trigger);
+        self
+    }
+
+    pub fn step(mut self, step: SequenceStep) -> Self {
+        self.params.steps.push(step);
+        self
+    }
+
+    pub fn email(self, template_id: impl Into<String>) -> Self {
+        self.step(SequenceStep::email(template_id))
+    }
+
+    /// An email step with its own subject line.
+    pub fn email_with_subject(
+        self,
+        template_id: impl Into<String>,
+        subject: impl Into<String>,
+    ) -> Self {
+        self.step(SequenceStep::new(StepAction::Email {
+            template_id: template_id.into(),
+            subject: Some(subject.into()),
+        }))
+    }
+
+    pub fn delay(self, amount: u32, unit: DelayUnit) -> Self {
+        self.step(SequenceStep::delay(amount, unit))
+    }
+
+    pub fn delay_hours(self, hours: u32) -> Self {
+        self.delay(hours, DelayUnit::Hours)
+    }
+
+    pub fn delay_days(self, days: u32) -> Self {
+        self.delay(days, DelayUnit::Days)
+    }
+
+    pub fn condition(self, condition: Condition) -> Self {
+        self.step(SequenceStep::condition(condition))
+    }
+
+    pub fn build(self) -> Result<SequenceParams> {
+        if self.params.name.trim().is_empty() {
+            return Err(invalid("name".to_string(), "sequence has no name"));
+        }
+        validate_steps(&self.params.steps)?;
+        Ok(self.params)
+    }
+}
 
 /// Automation sequence management.
 pub struct Sequences<'a> {
@@ -9,114 +467,154 @@ pub struct Sequences<'a> {
 }
 
 impl<'a> Sequences<'a> {
-    pub async fn create(&self, params: Value) -> Result<Value> {
-        self.http.post("/v1/sequences", Some(&params)).await
+    /// Create a sequence from [`SequenceParams`] or any JSON body. Steps in
+    /// the body are checked with [`validate_steps`] first.
+    pub async fn create(&self, params: impl Serialize) -> Result<Sequence> {
+        let params = serde_json::to_value(params)?;
+        if let Some(steps) = params.get("steps") {
+            validate_steps(&parse_steps(steps)?)?;
+        }
+        let response = self.http.post("/v1/sequences", Some(&params)).await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http.get("/v1/sequences", params).await
+    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Page<Sequence>> {
+        let response = self.http.get("/v1/sequences", params).await?;
+        Ok(serde_json::from_value(response)?)
     }
 
-    pub async fn get(&self, id: &str) -> Result<Value> {
-        self.http.get(&format!("/v1/sequences/{}", id), None).await
+    pub async fn get(&self, id: &str) -> Result<Sequence> {
+        let response = self
+            .http
+            .get(&format!("/v1/sequences/{}", id), None)
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn update(&self, id: &str, params: Value) -> Result<Value> {
-        self.http
+    /// Replace a sequence. Steps in the body are checked with
+    /// [`validate_steps`] first.
+    pub async fn update(&self, id: &str, params: impl Serialize) -> Result<Sequence> {
+        let params = serde_json::to_value(params)?;
+        if let Some(steps) = params.get("steps") {
+            validate_steps(&parse_steps(steps)?)?;
+        }
+        let response = self
+            .http
             .put(&format!("/v1/sequences/{}", id), &params)
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn delete(&self, id: &str) -> Result<()> {
         self.http.delete(&format!("/v1/sequences/{}", id)).await
     }
 
-    pub async fn activate(&self, id: &str) -> Result<Value> {
-        self.http
+    pub async fn activate(&self, id: &str) -> Result<Sequence> {
+        let response = self
+            .http
             .post(&format!("/v1/sequences/{}/activate", id), None)
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn pause(&self, id: &str) -> Result<Value> {
-        self.http
+    pub async fn pause(&self, id: &str) -> Result<Sequence> {
+        let response = self
+            .http
             .post(&format!("/v1/sequences/{}/pause", id), None)
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn archive(&self, id: &str) -> Result<Value> {
-        self.http
+    pub async fn archive(&self, id: &str) -> Result<Sequence> {
+        let response = self
+            .http
             .post(&format!("/v1/sequences/{}/archive", id), None)
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn add_step(&self, sequence_id: &str, params: Value) -> Result<Value> {
-        self.http
+    /// Append a step, checking it with [`SequenceStep::validate`] first.
+    pub async fn add_step(&self, sequence_id: &str, step: &SequenceStep) -> Result<SequenceStep> {
+        step.validate()?;
+        let response = self
+            .http
             .post(
                 &format!("/v1/sequences/{}/steps", sequence_id),
-                Some(&params),
+                Some(&serde_json::to_value(step)?),
             )
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
+    /// Replace a step, checking it with [`SequenceStep::validate`] first.
     pub async fn update_step(
         &self,
         sequence_id: &str,
         step_id: &str,
-        params: Value,
-    ) -> Result<Value> {
-        self.http
+        step: &SequenceStep,
+    ) -> Result<SequenceStep> {
+        step.validate()?;
+        let response = self
+            .http
             .put(
                 &format!("/v1/sequences/{}/steps/{}", sequence_id, step_id),
-                &params,
+                &serde_json::to_value(step)?,
             )
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn delete_step(&self, sequence_id: &str, step_id: &str) -> Result<()> {
         self.http
-            .delete(&format!(
-                "/v1/sequences/{}/steps/{}",
-                sequence_id, step_id
-            ))
+            .delete(&format!("/v1/sequences/{}/steps/{}", sequence_id, step_id))
             .await
     }
 
-    pub async fn reorder_steps(&self, sequence_id: &str, params: Value) -> Result<Value> {
-        self.http
+    /// Put the steps in the order of `step_ids`, which must list every step
+    /// of the sequence. Returns the steps in their new order.
+    pub async fn reorder_steps(
+        &self,
+        sequence_id: &str,
+        step_ids: &[impl AsRef<str>],
+    ) -> Result<Vec<SequenceStep>> {
+        let step_ids: Vec<&str> = step_ids.iter().map(AsRef::as_ref).collect();
+        let response = self
+            .http
             .post(
                 &format!("/v1/sequences/{}/steps/reorder", sequence_id),
-                Some(&params),
+                Some(&json!({ "stepIds": step_ids })),
             )
-            .await
+            .await?;
+        steps_from_response(response)
     }
 
-    pub async fn enroll(&self, sequence_id: &str, params: Value) -> Result<Value> {
-        self.http
+    pub async fn enroll(&self, sequence_id: &str, params: Value) -> Result<Enrollment> {
+        let response = self
+            .http
             .post(
                 &format!("/v1/sequences/{}/enroll", sequence_id),
                 Some(&params),
             )
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn list_enrollments(
         &self,
         sequence_id: &str,
         params: Option<&[(&str, &str)]>,
-    ) -> Result<Value> {
-        self.http
+    ) -> Result<Page<Enrollment>> {
+        let response = self
+            .http
             .get(
                 &format!("/v1/sequences/{}/enrollments", sequence_id),
                 params,
             )
-            .await
+            .await?;
+        Ok(serde_json::from_value(response)?)
     }
 
-    pub async fn remove_enrollment(
-        &self,
-        sequence_id: &str,
-        enrollment_id: &str,
-    ) -> Result<()> {
+    pub async fn remove_enrollment(&self, sequence_id: &str, enrollment_id: &str) -> Result<()> {
         self.http
             .delete(&format!(
                 "/v1/sequences/{}/enrollments/{}",
@@ -125,3 +623,182 @@ impl<'a> Sequences<'a> {
             .await
     }
 }
+
+fn parse_steps(steps: &Value) -> Result<Vec<SequenceStep>> {
+    serde_json::from_value(steps.clone())
+        .map_err(|e| invalid("steps".to_string(), &format!("invalid steps: {}", e)))
+}
+
+/// Accepts a bare array of steps, `{"steps": [...]}`, or a whole sequence,
+/// with or without a `data` wrapper.
+fn steps_from_response(response: Value) -> Result<Vec<SequenceStep>> {
+    let response = match response {
+        Value::Object(mut object) if object.contains_key("data") => {
+            object.remove("data").unwrap_or_default()
+        }
+        other => other,
+    };
+    let steps = match response {
+        Value::Object(mut object) => object.remove("steps").unwrap_or(Value::Object(object)),
+        other => other,
+    };
+    Ok(serde_json::from_value(steps)?)
+}
+
+fn invalid(field: String, message: &str) -> VeilMailError {
+    VeilMailError::invalid_input(field, message)
+}
+
+fn unwrap_data(value: Value) -> Value {
+    match value.get("data") {
+        Some(data) if data.is_object() => data.clone(),
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejected(steps: &[SequenceStep]) -> (String, String) {
+        match validate_steps(steps) {
+            Err(VeilMailError::InvalidInput { field, message }) => (field, message),
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+    }
+
+    fn email() -> SequenceStep {
+        SequenceStep::email("tmpl_welcome")
+    }
+
+    #[test]
+    fn valid_sequence_passes() {
+        let steps = [
+            email(),
+            SequenceStep::delay(2, DelayUnit::Days),
+            SequenceStep::condition(Condition::OpenedPrevious),
+            email(),
+        ];
+        assert!(validate_steps(&steps).is_ok());
+    }
+
+    #[test]
+    fn empty_sequence_is_rejected() {
+        assert_eq!(
+            rejected(&[]),
+            (
+                "steps".to_string(),
+                "sequence has no email step".to_string()
+            )
+        );
+        let err = SequenceBuilder::new("Empty").build().unwrap_err();
+        assert!(matches!(err, VeilMailError::InvalidInput { field, .. } if field == "steps"));
+    }
+
+    #[test]
+    fn zero_and_oversized_delays_are_rejected() {
+        let (field, message) =
+            rejected(&[email(), SequenceStep::delay(0, DelayUnit::Hours), email()]);
+        assert_eq!(field, "steps[1].amount");
+        assert_eq!(message, "delay must be at least 1");
+
+        let (field, message) =
+            rejected(&[email(), SequenceStep::delay(53, DelayUnit::Weeks), email()]);
+        assert_eq!(field, "steps[1].amount");
+        assert!(message.contains("365 days"));
+        assert!(
+            validate_steps(&[email(), SequenceStep::delay(365, DelayUnit::Days), email()]).is_ok()
+        );
+    }
+
+    #[test]
+    fn back_to_back_delays_are_rejected() {
+        let steps = [
+            email(),
+            SequenceStep::delay(1, DelayUnit::Days),
+            SequenceStep::delay(2, DelayUnit::Hours),
+            email(),
+        ];
+        let (field, message) = rejected(&steps);
+        assert_eq!(field, "steps[2]");
+        assert!(message.contains("two delays in a row"));
+
+        // A step in between makes them separate waits.
+        let steps = [
+            email(),
+            SequenceStep::delay(1, DelayUnit::Days),
+            email(),
+            SequenceStep::delay(2, DelayUnit::Hours),
+            email(),
+        ];
+        assert!(validate_steps(&steps).is_ok());
+    }
+
+    #[test]
+    fn condition_needs_an_earlier_email_and_a_following_step() {
+        let (field, message) =
+            rejected(&[SequenceStep::condition(Condition::ClickedPrevious), email()]);
+        assert_eq!(field, "steps[0]");
+        assert!(message.contains("no earlier email"));
+
+        // A property check does not depend on an earlier email.
+        let property = SequenceStep::condition(Condition::PropertyEquals {
+            property: "plan".to_string(),
+            value: json!("pro"),
+        });
+        assert!(validate_steps(&[property.clone(), email()]).is_ok());
+
+        let (field, message) = rejected(&[email(), property]);
+        assert_eq!(field, "steps[1]");
+        assert_eq!(message, "sequence ends with a condition");
+    }
+
+    #[test]
+    fn sequence_cannot_end_with_a_delay() {
+        let (field, message) = rejected(&[email(), SequenceStep::delay(1, DelayUnit::Days)]);
+        assert_eq!(field, "steps[1]");
+        assert_eq!(message, "sequence ends with a delay");
+    }
+
+    #[test]
+    fn builder_checks_name_and_steps() {
+        let err = SequenceBuilder::new(" ")
+            .email("tmpl_welcome")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, VeilMailError::InvalidInput { field, .. } if field == "name"));
+
+        let err = SequenceBuilder::new("Onboarding")
+            .email("tmpl_welcome")
+            .delay_hours(0)
+            .email("tmpl_next")
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(err, VeilMailError::InvalidInput { field, .. } if field == "steps[1].amount")
+        );
+
+        let params = SequenceBuilder::new("Onboarding")
+            .email_with_subject("tmpl_welcome", "Hi")
+            .delay_days(1)
+            .email("tmpl_next")
+            .build()
+            .unwrap();
+        assert_eq!(params.steps.len(), 3);
+    }
+
+    #[test]
+    fn reorder_response_shapes() {
+        let step = json!({ "id": "step_1", "position": 0, "type": "email", "templateId": "t" });
+        for response in [
+            json!([step]),
+            json!({ "data": [step] }),
+            json!({ "steps": [step] }),
+            json!({ "data": { "id": "seq_1", "steps": [step] } }),
+        ] {
+            let steps = steps_from_response(response.clone()).unwrap();
+            assert_eq!(steps[0].id.as_deref(), Some("step_1"), "{}", response);
+        }
+        assert!(steps_from_response(json!({ "data": { "ok": true } })).is_err());
+    }
+}
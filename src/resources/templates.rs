@@ -1,7 +1,323 @@
-use serde_json::Value;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
 
-use crate::error::Result;
+use crate::error::{Result, VeilMailError};
 use crate::http::HttpClient;
+use crate::pagination::Page;
+use crate::render::TemplateSource;
+
+/// A variable declared in a template's schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    pub name: String,
+    /// Whether sends must supply a value.
+    #[serde(default)]
+    pub required: bool,
+    /// Value used when `templateData` does not supply one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl TemplateVariable {
+    /// A required variable with no default.
+    pub fn required(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            required: true,
+            default_value: None,
+            description: None,
+        }
+    }
+
+    /// An optional variable with no default.
+    pub fn optional(name: impl Into<String>) -> Self {
+        Self {
+            required: false,
+            ..Self::required(name)
+        }
+    }
+
+    pub fn default_value(mut self, value: impl Into<Value>) -> Self {
+        self.default_value = Some(value.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// A stored email template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Declared variables. Older templates may list bare names, which are
+    /// read as optional variables.
+    #[serde(default, deserialize_with = "deserialize_variables")]
+    pub variables: Vec<TemplateVariable>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// A template rendered locally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedTemplate {
+    pub subject: Option<String>,
+    pub html: Option<String>,
+    pub text: Option<String>,
+}
+
+impl Template {
+    /// Render the subject, HTML and text parts the way the server would for
+    /// this `templateData`, applying declared defaults first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use veilmail::resources::templates::Template;
+    ///
+    /// let template: Template = serde_json::from_value(json!({
+    ///     "id": "tmpl_xxxxx",
+    ///     "name": "welcome",
+    ///     "subject": "Welcome, {{ name }}!",
+    ///     "html": "<p>{{#if plan}}You're on {{ plan }}.{{else}}Pick a plan.{{/if}}</p>",
+    ///     "variables": [
+    ///         { "name": "name", "required": true },
+    ///         { "name": "plan", "defaultValue": "Free" }
+    ///     ]
+    /// }))?;
+    ///
+    /// let rendered = template.render(&json!({ "name": "Alice" }))?;
+    /// assert_eq!(rendered.subject.as_deref(), Some("Welcome, Alice!"));
+    /// assert_eq!(rendered.html.as_deref(), Some("<p>You're on Free.</p>"));
+    ///
+    /// assert_eq!(template.missing_variables(&json!({}))?, ["name"]);
+    /// # Ok::<(), veilmail::error::VeilMailError>(())
+    /// ```
+    pub fn render(&self, data: &Value) -> Result<RenderedTemplate> {
+        let data = self.with_defaults(data);
+        let render = |part: &Option<String>, html: bool| -> Result<Option<String>> {
+            part.as_deref()
+                .map(|source| {
+                    let source = TemplateSource::parse(source)?;
+                    Ok(if html {
+                        source.render_html(&data)
+                    } else {
+                        source.render_text(&data)
+                    })
+                })
+                .transpose()
+        };
+
+        Ok(RenderedTemplate {
+            subject: render(&self.subject, false)?,
+            html: render(&self.html, true)?,
+            text: render(&self.text, false)?,
+        })
+    }
+
+    /// Variables that would render blank, plus required variables that have
+    /// no value and no default, in order of first use.
+    pub fn missing_variables(&self, data: &Value) -> Result<Vec<String>> {
+        let data = self.with_defaults(data);
+        let mut missing: Vec<String> = Vec::new();
+
+        for part in [&self.subject, &self.html, &self.text]
+            .into_iter()
+            .flatten()
+        {
+            for name in TemplateSource::parse(part)?.missing_variables(&data) {
+                if !missing.contains(&name) {
+                    missing.push(name);
+                }
+            }
+        }
+        for variable in self.variables.iter().filter(|v| v.required) {
+            let present = data.get(&variable.name).is_some_and(|v| !v.is_null());
+            if !present && !missing.contains(&variable.name) {
+                missing.push(variable.name.clone());
+            }
+        }
+
+        Ok(missing)
+    }
+
+    /// Names of the top-level variables the template uses: the declared
+    /// schema plus any variable referenced in the subject or bodies.
+    pub fn referenced_variables(&self) -> Result<BTreeSet<String>> {
+        let mut names: BTreeSet<String> = self.variables.iter().map(|v| v.name.clone()).collect();
+        for part in [&self.subject, &self.html, &self.text]
+            .into_iter()
+            .flatten()
+        {
+            for path in TemplateSource::parse(part)?.variables() {
+                let root = path.split('.').next().unwrap_or(&path);
+                names.insert(root.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Check that `T` supplies every variable this template needs and has no
+    /// fields the template ignores, which usually means a misspelled name.
+    ///
+    /// Variables with a declared default, or declared as optional, need not
+    /// be supplied.
+    pub fn check<T: VeilTemplate>(&self) -> Result<()> {
+        let provided: BTreeSet<&str> = T::VARIABLES.iter().copied().collect();
+        let referenced = self.referenced_variables()?;
+
+        let missing: Vec<String> = referenced
+            .iter()
+            .filter(|name| !provided.contains(name.as_str()))
+            .filter(
+                |name| match self.variables.iter().find(|v| &v.name == *name) {
+                    Some(declared) => declared.required && declared.default_value.is_none(),
+                    None => true,
+                },
+            )
+            .cloned()
+            .collect();
+        let unknown: Vec<String> = provided
+            .iter()
+            .filter(|name| !referenced.contains(**name))
+            .map(|name| name.to_string())
+            .collect();
+
+        if missing.is_empty() && unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(VeilMailError::TemplateMismatch {
+                template_id: self.id.clone(),
+                missing,
+                unknown,
+            })
+        }
+    }
+
+    fn with_defaults(&self, data: &Value) -> Value {
+        let mut merged = match data {
+            Value::Object(map) => map.clone(),
+            _ => Map::new(),
+        };
+        for variable in &self.variables {
+            if let Some(default) = &variable.default_value {
+                let present = merged.get(&variable.name).is_some_and(|v| !v.is_null());
+                if !present {
+                    merged.insert(variable.name.clone(), default.clone());
+                }
+            }
+        }
+        Value::Object(merged)
+    }
+}
+
+/// Fields for creating or updating a template.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateParams {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<TemplateVariable>,
+}
+
+impl TemplateParams {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn variable(mut self, variable: TemplateVariable) -> Self {
+        self.variables.push(variable);
+        self
+    }
+}
+
+/// A type whose fields are the `templateData` of one stored template.
+///
+/// Derive it with `#[derive(VeilTemplate)]` (the `derive` feature), send it
+/// with [`Emails::send_template`](crate::resources::emails::Emails::send_template),
+/// and check it against the server with [`Templates::validate`] at startup.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use serde::Serialize;
+/// use veilmail::VeilTemplate;
+///
+/// #[derive(Serialize, VeilTemplate)]
+/// #[veil_template(id = "tmpl_welcome")]
+/// #[serde(rename_all = "camelCase")]
+/// struct WelcomeEmail {
+///     name: String,
+///     login_url: String,
+///     #[serde(skip)]
+///     internal_note: String,
+/// }
+///
+/// assert_eq!(WelcomeEmail::TEMPLATE_ID, "tmpl_welcome");
+/// assert_eq!(WelcomeEmail::VARIABLES, ["name", "loginUrl"]);
+/// # }
+/// ```
+///
+/// Implementing it by hand is equivalent:
+///
+/// ```
+/// # use serde::Serialize;
+/// # #[derive(Serialize)]
+/// # #[serde(rename_all = "camelCase")]
+/// # struct WelcomeEmail { name: String, login_url: String }
+/// impl veilmail::VeilTemplate for WelcomeEmail {
+///     const TEMPLATE_ID: &'static str = "tmpl_welcome";
+///     const VARIABLES: &'static [&'static str] = &["name", "loginUrl"];
+/// }
+/// ```
+pub trait VeilTemplate: Serialize {
+    /// ID of the stored template.
+    const TEMPLATE_ID: &'static str;
+    /// Serialized names of the fields, i.e. the variables this type supplies.
+    const VARIABLES: &'static [&'static str];
+}
 
 /// Email template management.
 pub struct Templates<'a> {
@@ -9,31 +325,66 @@ pub struct Templates<'a> {
 }
 
 impl<'a> Templates<'a> {
-    pub async fn create(&self, params: Value) -> Result<Value> {
-        let response = self.http.post("/v1/templates", Some(&params)).await?;
-        Ok(unwrap_data(response))
+    /// Create a template from [`TemplateParams`] or a raw JSON body.
+    pub async fn create(&self, params: impl Serialize) -> Result<Template> {
+        let body = serde_json::to_value(params)?;
+        let response = self.http.post("/v1/templates", Some(&body)).await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
         self.http.get("/v1/templates", params).await
     }
 
-    pub async fn get(&self, id: &str) -> Result<Value> {
+    /// Fetch every template, following pagination cursors.
+    pub async fn list_all(&self) -> Result<Vec<Template>> {
+        let mut templates = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = vec![("limit", "100")];
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor));
+            }
+            let response = self.http.get("/v1/templates", Some(&query)).await?;
+            let page: Page<Template> = serde_json::from_value(response)?;
+
+            cursor = page.next().map(str::to_string);
+            templates.extend(page);
+            if cursor.is_none() {
+                return Ok(templates);
+            }
+        }
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Template> {
         let response = self
             .http
             .get(&format!("/v1/templates/{}", id), None)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn update(&self, id: &str, params: Value) -> Result<Value> {
+    /// Update a template from [`TemplateParams`] or a raw JSON body.
+    pub async fn update(&self, id: &str, params: impl Serialize) -> Result<Template> {
+        let body = serde_json::to_value(params)?;
         let response = self
             .http
-            .patch(&format!("/v1/templates/{}", id), &params)
+            .patch(&format!("/v1/templates/{}", id), &body)
             .await?;
-        Ok(unwrap_data(response))
+        Ok(serde_json::from_value(unwrap_data(response))?)
+    }
+
+    /// Fetch `T`'s template and [check](Template::check) that `T` matches it.
+    /// Call once at startup for each template type.
+    pub async fn validate<T: VeilTemplate>(&self) -> Result<Template> {
+        let template = self.get(T::TEMPLATE_ID).await?;
+        template.check::<T>()?;
+        Ok(template)
     }
 
+    /// Render a stored template on the server. See [`Template::render`] to
+    /// render locally.
     pub async fn preview(&self, params: Value) -> Result<Value> {
         self.http.post("/v1/templates/preview", Some(&params)).await
     }
@@ -43,6 +394,29 @@ impl<'a> Templates<'a> {
     }
 }
 
+pub(crate) fn deserialize_variables<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<TemplateVariable>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Name(String),
+        Variable(TemplateVariable),
+    }
+
+    let entries = Option::<Vec<Entry>>::deserialize(deserializer)?.unwrap_or_default();
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            Entry::Name(name) => TemplateVariable::optional(name),
+            Entry::Variable(variable) => variable,
+        })
+        .collect())
+}
+
 fn unwrap_data(value: Value) -> Value {
     match value.get("data") {
         Some(data) if data.is_object() => data.clone(),
@@ -1,7 +1,239 @@
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
 use crate::error::Result;
 use crate::http::HttpClient;
+use crate::pagination::Page;
+
+/// A subscription topic subscribers can opt in to or out of.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Topic {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether subscribers with no explicit choice receive this topic.
+    #[serde(default, alias = "defaultSubscribed")]
+    pub default_opt_in: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// A subscriber's explicit choice for one topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicPreference {
+    pub topic_id: String,
+    pub subscribed: bool,
+}
+
+/// A subscriber's explicit topic choices. Topics not listed use their
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicPreferences {
+    #[serde(default)]
+    pub topics: Vec<TopicPreference>,
+}
+
+impl TopicPreferences {
+    /// The explicit choice for `topic_id`, if any.
+    pub fn get(&self, topic_id: &str) -> Option<bool> {
+        self.topics
+            .iter()
+            .find(|p| p.topic_id == topic_id)
+            .map(|p| p.subscribed)
+    }
+
+    /// These choices with `update` applied on top.
+    fn with_update(&self, update: &PreferenceUpdate) -> Self {
+        let mut choices: BTreeMap<&str, bool> = self
+            .topics
+            .iter()
+            .map(|p| (p.topic_id.as_str(), p.subscribed))
+            .collect();
+        for (topic_id, subscribed) in update.changes() {
+            choices.insert(topic_id, subscribed);
+        }
+        Self {
+            topics: choices
+                .into_iter()
+                .map(|(topic_id, subscribed)| TopicPreference {
+                    topic_id: topic_id.to_string(),
+                    subscribed,
+                })
+                .collect(),
+        }
+    }
+
+    /// Accepts `{"topics": [...]}`, a bare array, or either wrapped in
+    /// `data`.
+    fn from_response(response: Value) -> Result<Self> {
+        let response = match response {
+            Value::Object(mut object) if object.contains_key("data") => {
+                object.remove("data").unwrap_or_default()
+            }
+            other => other,
+        };
+        Ok(match response {
+            Value::Array(_) => Self {
+                topics: serde_json::from_value(response)?,
+            },
+            other => serde_json::from_value(other)?,
+        })
+    }
+}
+
+/// How a subscriber's state for a topic was decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreferenceState {
+    OptedIn,
+    OptedOut,
+    /// No explicit choice; the topic's `default_opt_in` applies.
+    Default,
+}
+
+/// One row of a [`PreferenceCenter`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicChoice {
+    pub topic: Topic,
+    pub state: PreferenceState,
+}
+
+impl TopicChoice {
+    /// Whether the subscriber currently receives this topic.
+    pub fn is_subscribed(&self) -> bool {
+        match self.state {
+            PreferenceState::OptedIn => true,
+            PreferenceState::OptedOut => false,
+            PreferenceState::Default => self.topic.default_opt_in,
+        }
+    }
+}
+
+/// Every topic alongside one subscriber's choice, for rendering a
+/// preferences page. Built by [`Topics::preference_center`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferenceCenter {
+    pub audience_id: String,
+    pub subscriber_id: String,
+    pub topics: Vec<TopicChoice>,
+}
+
+impl PreferenceCenter {
+    /// Merge all topics with a subscriber's explicit preferences. Choices
+    /// for topics that no longer exist are dropped.
+    pub fn merge(
+        audience_id: impl Into<String>,
+        subscriber_id: impl Into<String>,
+        topics: Vec<Topic>,
+        preferences: &TopicPreferences,
+    ) -> Self {
+        let topics = topics
+            .into_iter()
+            .map(|topic| {
+                let state = match preferences.get(&topic.id) {
+                    Some(true) => PreferenceState::OptedIn,
+                    Some(false) => PreferenceState::OptedOut,
+                    None => PreferenceState::Default,
+                };
+                TopicChoice { topic, state }
+            })
+            .collect();
+        Self {
+            audience_id: audience_id.into(),
+            subscriber_id: subscriber_id.into(),
+            topics,
+        }
+    }
+
+    pub fn topic(&self, topic_id: &str) -> Option<&TopicChoice> {
+        self.topics.iter().find(|c| c.topic.id == topic_id)
+    }
+
+    /// The changes needed to reach `desired`, e.g. the checkboxes submitted
+    /// from a form. Topics already in the desired state, including through
+    /// their default, are left out.
+    ///
+    /// ```
+    /// use veilmail::resources::topics::{PreferenceCenter, Topic, TopicPreferences};
+    ///
+    /// let topic = |id: &str, default_opt_in| Topic {
+    ///     id: id.into(),
+    ///     name: id.into(),
+    ///     description: None,
+    ///     default_opt_in,
+    ///     created_at: None,
+    ///     updated_at: None,
+    /// };
+    /// let center = PreferenceCenter::merge(
+    ///     "audience_xxxxx",
+    ///     "sub_xxxxx",
+    ///     vec![topic("news", true), topic("offers", false)],
+    ///     &TopicPreferences::default(),
+    /// );
+    ///
+    /// let update = center.changes_to([("news", true), ("offers", true)]);
+    /// assert_eq!(update.changes().collect::<Vec<_>>(), [("offers", true)]);
+    /// ```
+    pub fn changes_to<I, S>(&self, desired: I) -> PreferenceUpdate
+    where
+        I: IntoIterator<Item = (S, bool)>,
+        S: Into<String>,
+    {
+        let mut update = PreferenceUpdate::new();
+        for (topic_id, subscribed) in desired {
+            let topic_id = topic_id.into();
+            let current = self.topic(&topic_id).map(TopicChoice::is_subscribed);
+            if current != Some(subscribed) {
+                update = update.set(topic_id, subscribed);
+            }
+        }
+        update
+    }
+}
+
+/// Topic choices to change, for [`Topics::apply_preferences`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreferenceUpdate {
+    changes: BTreeMap<String, bool>,
+}
+
+impl PreferenceUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(self, topic_id: impl Into<String>) -> Self {
+        self.set(topic_id, true)
+    }
+
+    pub fn unsubscribe(self, topic_id: impl Into<String>) -> Self {
+        self.set(topic_id, false)
+    }
+
+    pub fn set(mut self, topic_id: impl Into<String>, subscribed: bool) -> Self {
+        self.changes.insert(topic_id.into(), subscribed);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// `(topic_id, subscribed)` pairs, sorted by topic ID.
+    pub fn changes(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.changes.iter().map(|(id, s)| (id.as_str(), *s))
+    }
+}
 
 /// Subscription topic management.
 pub struct Topics<'a> {
@@ -9,30 +241,58 @@ pub struct Topics<'a> {
 }
 
 impl<'a> Topics<'a> {
-    pub async fn create(&self, params: Value) -> Result<Value> {
-        self.http.post("/v1/topics", Some(&params)).await
+    pub async fn create(&self, params: Value) -> Result<Topic> {
+        let response = self.http.post("/v1/topics", Some(&params)).await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
+    }
+
+    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Page<Topic>> {
+        let response = self.http.get("/v1/topics", params).await?;
+        Ok(serde_json::from_value(response)?)
     }
 
-    pub async fn list(&self, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        self.http.get("/v1/topics", params).await
+    /// Every topic, following pagination cursors.
+    pub async fn list_all(&self) -> Result<Vec<Topic>> {
+        let mut topics = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = vec![("limit", "100")];
+            if let Some(cursor) = &cursor {
+                params.push(("cursor", cursor.as_str()));
+            }
+            let page = self.list(Some(&params)).await?;
+            cursor = page.next().map(str::to_string);
+            topics.extend(page.data);
+            if cursor.is_none() {
+                return Ok(topics);
+            }
+        }
     }
 
-    pub async fn get(&self, id: &str) -> Result<Value> {
-        self.http.get(&format!("/v1/topics/{}", id), None).await
+    pub async fn get(&self, id: &str) -> Result<Topic> {
+        let response = self.http.get(&format!("/v1/topics/{}", id), None).await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
-    pub async fn update(&self, id: &str, params: Value) -> Result<Value> {
-        self.http
+    pub async fn update(&self, id: &str, params: Value) -> Result<Topic> {
+        let response = self
+            .http
             .patch(&format!("/v1/topics/{}", id), &params)
-            .await
+            .await?;
+        Ok(serde_json::from_value(unwrap_data(response))?)
     }
 
     pub async fn delete(&self, id: &str) -> Result<()> {
         self.http.delete(&format!("/v1/topics/{}", id)).await
     }
 
-    pub async fn get_preferences(&self, audience_id: &str, subscriber_id: &str) -> Result<Value> {
-        self.http
+    pub async fn get_preferences(
+        &self,
+        audience_id: &str,
+        subscriber_id: &str,
+    ) -> Result<TopicPreferences> {
+        let response = self
+            .http
             .get(
                 &format!(
                     "/v1/audiences/{}/subscribers/{}/topics",
@@ -40,23 +300,100 @@ impl<'a> Topics<'a> {
                 ),
                 None,
             )
-            .await
+            .await?;
+        TopicPreferences::from_response(response)
     }
 
+    /// Replace a subscriber's explicit choices. `preferences` is usually a
+    /// [`TopicPreferences`], but any value that serializes to the same
+    /// `{"topics": [...]}` body works.
     pub async fn set_preferences(
         &self,
         audience_id: &str,
         subscriber_id: &str,
-        params: Value,
-    ) -> Result<Value> {
-        self.http
+        preferences: &impl Serialize,
+    ) -> Result<TopicPreferences> {
+        let response = self
+            .http
             .put(
                 &format!(
                     "/v1/audiences/{}/subscribers/{}/topics",
                     audience_id, subscriber_id
                 ),
-                &params,
+                &json!(preferences),
             )
-            .await
+            .await?;
+        TopicPreferences::from_response(response)
+    }
+
+    /// All topics merged with one subscriber's choices.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+    /// let topics = client.topics();
+    /// let center = topics.preference_center("audience_xxxxx", "sub_xxxxx").await?;
+    /// for choice in &center.topics {
+    ///     println!("[{}] {}", if choice.is_subscribed() { "x" } else { " " }, choice.topic.name);
+    /// }
+    ///
+    /// // After the form is submitted:
+    /// let update = center.changes_to([("topic_news", false), ("topic_offers", true)]);
+    /// let center = topics.apply_preferences(&center, &update).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn preference_center(
+        &self,
+        audience_id: &str,
+        subscriber_id: &str,
+    ) -> Result<PreferenceCenter> {
+        let topics = self.list_all().await?;
+        let preferences = self.get_preferences(audience_id, subscriber_id).await?;
+        Ok(PreferenceCenter::merge(
+            audience_id,
+            subscriber_id,
+            topics,
+            &preferences,
+        ))
+    }
+
+    /// Apply `update` to the subscriber's current choices, returning the
+    /// refreshed view. The choices are fetched again right before saving,
+    /// so changes made since `center` was loaded are kept; `center` only
+    /// supplies the subscriber and the topic list. Does nothing if `update`
+    /// is empty.
+    pub async fn apply_preferences(
+        &self,
+        center: &PreferenceCenter,
+        update: &PreferenceUpdate,
+    ) -> Result<PreferenceCenter> {
+        if update.is_empty() {
+            return Ok(center.clone());
+        }
+        let current = self
+            .get_preferences(&center.audience_id, &center.subscriber_id)
+            .await?;
+        let saved = self
+            .set_preferences(
+                &center.audience_id,
+                &center.subscriber_id,
+                &current.with_update(update),
+            )
+            .await?;
+        Ok(PreferenceCenter::merge(
+            center.audience_id.clone(),
+            center.subscriber_id.clone(),
+            center.topics.iter().map(|c| c.topic.clone()).collect(),
+            &saved,
+        ))
+    }
+}
+
+fn unwrap_data(value: Value) -> Value {
+    match value.get("data") {
+        Some(data) if data.is_object() => data.clone(),
+        _ => value,
     }
 }
@@ -0,0 +1,372 @@
+//! Typed send times.
+//!
+//! A [`SendAt`] is a single instant. It can be built from [`SystemTime`], or
+//! from `chrono` / `time` datetimes with the `chrono` and `time` features.
+//! Only types that carry a timezone or offset convert, so a naive datetime
+//! cannot be sent at the wrong offset by accident.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use std::time::Duration;
+//! use veilmail::schedule::SendAt;
+//!
+//! let at = SendAt::after(Duration::from_secs(3_600));
+//! client.campaigns().schedule_at("campaign_xxxxx", at).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, VeilMailError};
+
+/// When to send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SendAt {
+    at: SystemTime,
+}
+
+impl SendAt {
+    pub fn instant(at: SystemTime) -> Self {
+        SendAt { at }
+    }
+
+    /// `delay` from now.
+    pub fn after(delay: Duration) -> Self {
+        SendAt::instant(SystemTime::now() + delay)
+    }
+
+    pub fn system_time(&self) -> SystemTime {
+        self.at
+    }
+
+    /// Check that the send time is still ahead of `now`.
+    ///
+    /// The API takes whole seconds, so this checks the truncated time that
+    /// [`to_params`](Self::to_params) sends, not the original.
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use veilmail::schedule::SendAt;
+    ///
+    /// let now = SystemTime::now();
+    /// assert!(SendAt::instant(now + Duration::from_secs(60)).validate(now).is_ok());
+    /// assert!(SendAt::instant(now - Duration::from_secs(60)).validate(now).is_err());
+    /// ```
+    pub fn validate(&self, now: SystemTime) -> Result<()> {
+        let secs = self.timestamp()?;
+        if UNIX_EPOCH + Duration::from_secs(secs as u64) <= now {
+            return Err(invalid(format!(
+                "send time {} is not in the future",
+                rfc3339(secs)
+            )));
+        }
+        Ok(())
+    }
+
+    /// The request body for scheduling.
+    pub fn to_params(&self) -> Result<Value> {
+        Ok(json!({ "scheduledAt": rfc3339(self.timestamp()?) }))
+    }
+
+    /// Whole seconds since the Unix epoch; the value sent to the API.
+    fn timestamp(&self) -> Result<i64> {
+        self.at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .map_err(|_| invalid("send time is before 1970".to_string()))
+    }
+}
+
+impl From<SystemTime> for SendAt {
+    fn from(at: SystemTime) -> Self {
+        SendAt::instant(at)
+    }
+}
+
+impl FromStr for SendAt {
+    type Err = VeilMailError;
+
+    /// Parse an RFC 3339 timestamp with an offset, e.g.
+    /// `2027-03-02T09:00:00Z` or `2027-03-02T09:00:00.5-05:00`.
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use veilmail::schedule::SendAt;
+    ///
+    /// let at: SendAt = "2027-03-02T09:00:00-05:00".parse().unwrap();
+    /// assert_eq!(at.system_time(), UNIX_EPOCH + Duration::from_secs(1_803_996_000));
+    /// assert!("2027-03-02T09:00:00".parse::<SendAt>().is_err()); // no offset
+    /// ```
+    fn from_str(value: &str) -> Result<Self> {
+        let error = || {
+            invalid(format!(
+                "`{}` is not an RFC 3339 timestamp like 2027-03-02T09:00:00Z",
+                value
+            ))
+        };
+        let bytes = value.as_bytes();
+        let number = |range: std::ops::Range<usize>| -> Result<u32> {
+            let digits = value.get(range).ok_or_else(error)?;
+            if digits.bytes().all(|b| b.is_ascii_digit()) {
+                digits.parse().map_err(|_| error())
+            } else {
+                Err(error())
+            }
+        };
+        if bytes.len() < 20
+            || bytes[4] != b'-'
+            || bytes[7] != b'-'
+            || !matches!(bytes[10], b'T' | b't' | b' ')
+            || bytes[13] != b':'
+            || bytes[16] != b':'
+        {
+            return Err(error());
+        }
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(error());
+        }
+
+        // Fractional seconds, then the offset.
+        let mut rest = 19;
+        let mut nanos = 0u32;
+        if bytes[rest] == b'.' {
+            let digits = bytes[rest + 1..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if digits == 0 {
+                return Err(error());
+            }
+            for (i, b) in bytes[rest + 1..rest + 1 + digits]
+                .iter()
+                .take(9)
+                .enumerate()
+            {
+                nanos += u32::from(b - b'0') * 10u32.pow(8 - i as u32);
+            }
+            rest += 1 + digits;
+        }
+        let offset = match value.get(rest..).ok_or_else(error)? {
+            "Z" | "z" => 0,
+            tail if tail.len() == 6 && tail.as_bytes()[3] == b':' => {
+                let (hours, minutes) = (number(rest + 1..rest + 3)?, number(rest + 4..rest + 6)?);
+                if hours > 23 || minutes > 59 {
+                    return Err(error());
+                }
+                let offset = i64::from(hours * 3_600 + minutes * 60);
+                match tail.as_bytes()[0] {
+                    b'+' => offset,
+                    b'-' => -offset,
+                    _ => return Err(error()),
+                }
+            }
+            _ => return Err(error()),
+        };
+
+        let secs = days_from_civil(year.into(), month.into(), day.into()) * 86_400
+            + i64::from(hour * 3_600 + minute * 60 + second)
+            - offset;
+        let secs = u64::try_from(secs).map_err(|_| error())?;
+        Ok(SendAt::instant(UNIX_EPOCH + Duration::new(secs, nanos)))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for SendAt {
+    fn from(at: chrono::DateTime<Tz>) -> Self {
+        SendAt::instant(at.into())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for SendAt {
+    fn from(at: time::OffsetDateTime) -> Self {
+        SendAt::instant(at.into())
+    }
+}
+
+fn invalid(message: String) -> VeilMailError {
+    VeilMailError::invalid_input("scheduledAt", message)
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Format a Unix timestamp as an RFC 3339 UTC string.
+pub(crate) fn rfc3339(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Result<SendAt> {
+        value.parse()
+    }
+
+    fn at(secs: u64, nanos: u32) -> SendAt {
+        SendAt::instant(UNIX_EPOCH + Duration::new(secs, nanos))
+    }
+
+    #[test]
+    fn parses_utc_designator_in_either_case() {
+        assert_eq!(parse("2024-02-29T12:00:00Z").unwrap(), at(1_709_208_000, 0));
+        assert_eq!(parse("2024-02-29t12:00:00z").unwrap(), at(1_709_208_000, 0));
+        assert_eq!(parse("2024-02-29 12:00:00Z").unwrap(), at(1_709_208_000, 0));
+    }
+
+    #[test]
+    fn applies_offsets() {
+        assert_eq!(
+            parse("2024-02-29T17:30:00+05:30").unwrap(),
+            at(1_709_208_000, 0)
+        );
+        assert_eq!(
+            parse("2024-02-29T07:00:00-05:00").unwrap(),
+            at(1_709_208_000, 0)
+        );
+        assert_eq!(
+            parse("2024-02-29T12:00:00+00:00").unwrap(),
+            parse("2024-02-29T12:00:00-00:00").unwrap()
+        );
+        // The offset can move the instant across a day boundary.
+        assert_eq!(
+            parse("2024-03-01T01:00:00+13:00").unwrap(),
+            at(1_709_208_000, 0)
+        );
+    }
+
+    #[test]
+    fn keeps_up_to_nine_fractional_digits() {
+        assert_eq!(
+            parse("2024-02-29T12:00:00.5Z").unwrap(),
+            at(1_709_208_000, 500_000_000)
+        );
+        assert_eq!(
+            parse("2024-02-29T12:00:00.123456789Z").unwrap(),
+            at(1_709_208_000, 123_456_789)
+        );
+        assert_eq!(
+            parse("2024-02-29T12:00:00.1234567891234+00:00").unwrap(),
+            at(1_709_208_000, 123_456_789)
+        );
+        assert!(parse("2024-02-29T12:00:00.Z").is_err());
+    }
+
+    #[test]
+    fn february_29_only_in_leap_years() {
+        assert_eq!(parse("2000-02-29T00:00:00Z").unwrap(), at(951_782_400, 0));
+        assert!(parse("2024-02-29T00:00:00Z").is_ok());
+        assert!(parse("2023-02-29T00:00:00Z").is_err());
+        assert!(parse("2100-02-29T00:00:00Z").is_err());
+        assert!(parse("2024-02-30T00:00:00Z").is_err());
+    }
+
+    #[test]
+    fn rejects_times_before_1970() {
+        assert_eq!(parse("1970-01-01T00:00:00Z").unwrap(), at(0, 0));
+        assert!(parse("1969-12-31T23:59:59Z").is_err());
+        assert!(parse("1970-01-01T00:30:00+01:00").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for value in [
+            "",
+            "2027-03-02",
+            "2027-03-02T09:00:00",
+            "2027-03-02T09:00Z",
+            "2027-03-02T09:00:00+0500",
+            "2027-03-02T09:00:00+05",
+            "2027-03-02T09:00:00*05:00",
+            "2027-03-02T09:00:00+24:00",
+            "2027-03-02T09:00:00Zjunk",
+            "2027-03-02X09:00:00Z",
+            "2027/03/02T09:00:00Z",
+            "2027-3-02T09:00:00Z",
+            "2027-+3-02T09:00:00Z",
+            "2027-00-02T09:00:00Z",
+            "2027-13-02T09:00:00Z",
+            "2027-03-00T09:00:00Z",
+            "2027-04-31T09:00:00Z",
+            "2027-03-02T24:00:00Z",
+            "2027-03-02T09:60:00Z",
+            "2027-03-02T09:00:60Z",
+            "2027-03-02T09:00:00.5",
+            "2027-03-02T09:00:0\u{e9}Z",
+        ] {
+            match parse(value) {
+                Err(VeilMailError::InvalidInput { field, .. }) => {
+                    assert_eq!(field, "scheduledAt", "{:?}", value)
+                }
+                other => panic!("expected InvalidInput for {:?}, got {:?}", value, other),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_and_params_use_whole_seconds() {
+        let sent = at(1_709_208_000, 900_000_000);
+        assert_eq!(
+            sent.to_params().unwrap(),
+            json!({ "scheduledAt": "2024-02-29T12:00:00Z" })
+        );
+        let now = UNIX_EPOCH + Duration::new(1_709_208_000, 500_000_000);
+        assert!(sent.validate(now).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+//! Enroll many subscribers at once and move them between sequences.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! let emails = ["ada@example.com", "grace@example.com"];
+//! let report = client
+//!     .sequences()
+//!     .enroll_many("seq_onboarding", emails, 8)
+//!     .await;
+//! for (target, error) in report.failed() {
+//!     eprintln!("{}: {}", target, error);
+//! }
+//!
+//! let moved = client
+//!     .sequences()
+//!     .move_subscribers("seq_trial", "seq_customer", ["sub_xxxxx"], 4)
+//!     .await?;
+//! println!("{} moved", moved.moved().count());
+//! # Ok(())
+//! # }
+//! ```
+
+use futures_util::stream::{self, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{Result, VeilMailError};
+use crate::pagination::Page;
+use crate::resources::sequences::{Enrollment, EnrollmentStatus, Sequences};
+
+const PAGE_SIZE: &str = "100";
+
+/// Who to enroll. Converting from a string picks [`Email`](Self::Email) if
+/// it contains `@` and [`SubscriberId`](Self::SubscriberId) otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EnrollTarget {
+    SubscriberId(String),
+    Email(String),
+}
+
+impl EnrollTarget {
+    fn body(&self) -> Value {
+        match self {
+            EnrollTarget::SubscriberId(id) => json!({ "subscriberId": id }),
+            EnrollTarget::Email(email) => json!({ "email": email }),
+        }
+    }
+}
+
+impl From<&str> for EnrollTarget {
+    fn from(s: &str) -> Self {
+        s.to_string().into()
+    }
+}
+
+impl From<String> for EnrollTarget {
+    fn from(s: String) -> Self {
+        if s.contains('@') {
+            EnrollTarget::Email(s)
+        } else {
+            EnrollTarget::SubscriberId(s)
+        }
+    }
+}
+
+impl fmt::Display for EnrollTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnrollTarget::SubscriberId(id) => f.write_str(id),
+            EnrollTarget::Email(email) => f.write_str(email),
+        }
+    }
+}
+
+/// The result of enrolling one target.
+#[derive(Debug)]
+pub struct EnrollOutcome {
+    pub target: EnrollTarget,
+    pub result: Result<Enrollment>,
+}
+
+/// Outcome of [`Sequences::enroll_many`], in input order.
+#[derive(Debug, Default)]
+pub struct BulkEnrollReport {
+    pub outcomes: Vec<EnrollOutcome>,
+}
+
+impl BulkEnrollReport {
+    pub fn enrolled(&self) -> impl Iterator<Item = &Enrollment> {
+        self.outcomes.iter().filter_map(|o| o.result.as_ref().ok())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = (&EnrollTarget, &VeilMailError)> {
+        self.outcomes
+            .iter()
+            .filter_map(|o| o.result.as_ref().err().map(|e| (&o.target, e)))
+    }
+
+    /// Whether every target was enrolled.
+    pub fn is_complete(&self) -> bool {
+        self.outcomes.iter().all(|o| o.result.is_ok())
+    }
+}
+
+/// What happened to one subscriber in [`Sequences::move_subscribers`].
+#[derive(Debug)]
+pub enum MoveOutcome {
+    /// Enrolled in the target sequence and removed from the source.
+    Moved {
+        from: Box<Enrollment>,
+        to: Box<Enrollment>,
+    },
+    /// No active enrollment in the source sequence; nothing was done.
+    NotEnrolled,
+    /// The move failed. Unless `left_in_both` is set, the subscriber is
+    /// still only in the source sequence.
+    Failed {
+        error: VeilMailError,
+        /// Removing the subscriber from the source failed and so did
+        /// undoing the new enrollment.
+        left_in_both: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct MoveResult {
+    pub target: EnrollTarget,
+    pub outcome: MoveOutcome,
+}
+
+/// Outcome of [`Sequences::move_subscribers`], in input order.
+#[derive(Debug, Default)]
+pub struct MoveReport {
+    pub results: Vec<MoveResult>,
+}
+
+impl MoveReport {
+    pub fn moved(&self) -> impl Iterator<Item = &MoveResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, MoveOutcome::Moved { .. }))
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &MoveResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, MoveOutcome::Failed { .. }))
+    }
+}
+
+impl Sequences<'_> {
+    /// Enroll every target, running up to `concurrency` requests at once.
+    ///
+    /// Failures are reported per target rather than stopping the batch.
+    pub async fn enroll_many<I, T>(
+        &self,
+        sequence_id: &str,
+        targets: I,
+        concurrency: usize,
+    ) -> BulkEnrollReport
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<EnrollTarget>,
+    {
+        let outcomes = stream::iter(targets.into_iter().map(Into::into))
+            .map(|target: EnrollTarget| async move {
+                let result = self.enroll(sequence_id, target.body()).await;
+                EnrollOutcome { target, result }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        BulkEnrollReport { outcomes }
+    }
+
+    /// Move subscribers from one sequence to another.
+    ///
+    /// Each subscriber is enrolled in `to` before being removed from
+    /// `from`. If the removal fails, the new enrollment is removed again, so
+    /// a subscriber ends up in exactly one of the two sequences unless that
+    /// cleanup fails too (reported as `left_in_both`). Only active
+    /// enrollments in `from` are moved.
+    ///
+    /// Fails only if the enrollments of `from` cannot be listed.
+    pub async fn move_subscribers<I, T>(
+        &self,
+        from: &str,
+        to: &str,
+        targets: I,
+        concurrency: usize,
+    ) -> Result<MoveReport>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<EnrollTarget>,
+    {
+        let current = self.active_enrollments(from).await?;
+
+        let results = stream::iter(targets.into_iter().map(Into::into))
+            .map(|target: EnrollTarget| {
+                let existing = current.get(&key(&target)).cloned();
+                async move {
+                    let outcome = match existing {
+                        None => MoveOutcome::NotEnrolled,
+                        Some(existing) => self.move_one(from, to, &target, existing).await,
+                    };
+                    MoveResult { target, outcome }
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        Ok(MoveReport { results })
+    }
+
+    async fn move_one(
+        &self,
+        from: &str,
+        to: &str,
+        target: &EnrollTarget,
+        existing: Enrollment,
+    ) -> MoveOutcome {
+        let enrolled = match self.enroll(to, target.body()).await {
+            Ok(enrolled) => enrolled,
+            Err(error) => {
+                return MoveOutcome::Failed {
+                    error,
+                    left_in_both: false,
+                }
+            }
+        };
+
+        match self.remove_enrollment(from, &existing.id).await {
+            Ok(()) => MoveOutcome::Moved {
+                from: Box::new(existing),
+                to: Box::new(enrolled),
+            },
+            Err(error) => MoveOutcome::Failed {
+                error,
+                left_in_both: self.remove_enrollment(to, &enrolled.id).await.is_err(),
+            },
+        }
+    }
+
+    /// Active enrollments of a sequence, keyed by subscriber ID and by
+    /// lowercased email.
+    async fn active_enrollments(&self, sequence_id: &str) -> Result<HashMap<String, Enrollment>> {
+        let mut enrollments = HashMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = vec![("limit", PAGE_SIZE)];
+            if let Some(cursor) = &cursor {
+                params.push(("cursor", cursor.as_str()));
+            }
+            let page: Page<Enrollment> = self.list_enrollments(sequence_id, Some(&params)).await?;
+            cursor = page.next().map(str::to_string);
+
+            for enrollment in page {
+                if enrollment.status != EnrollmentStatus::Active {
+                    continue;
+                }
+                if let Some(id) = &enrollment.subscriber_id {
+                    enrollments.insert(id.clone(), enrollment.clone());
+                }
+                if let Some(email) = &enrollment.email {
+                    enrollments.insert(email.to_lowercase(), enrollment);
+                }
+            }
+            if cursor.is_none() {
+                return Ok(enrollments);
+            }
+        }
+    }
+}
+
+fn key(target: &EnrollTarget) -> String {
+    match target {
+        EnrollTarget::SubscriberId(id) => id.clone(),
+        EnrollTarget::Email(email) => email.to_lowercase(),
+    }
+}
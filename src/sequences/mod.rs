@@ -0,0 +1,3 @@
+//! Bulk operations on sequence enrollments.
+
+pub mod enrollment;
@@ -0,0 +1,237 @@
+//! Streaming subscriber export.
+//!
+//! Subscribers are fetched a page at a time, so memory use stays flat no
+//! matter how large the audience is.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use futures_util::TryStreamExt;
+//! use veilmail::resources::audiences::{SubscriberFilter, SubscriberStatus};
+//! use veilmail::subscribers::export::{ExportFormat, SubscriberExport};
+//!
+//! let subs = client.audiences().subscribers("audience_xxxxx");
+//!
+//! // As typed records
+//! let mut stream = Box::pin(subs.stream(SubscriberFilter::new().status(SubscriberStatus::Active)));
+//! while let Some(subscriber) = stream.try_next().await? {
+//!     println!("{}", subscriber.email);
+//! }
+//!
+//! // Straight to a file
+//! let mut file = tokio::fs::File::create("subscribers.ndjson").await.unwrap();
+//! let options = SubscriberExport::new(ExportFormat::Ndjson).columns(["email", "status", "properties.plan"]);
+//! let written = subs.export_to(&mut file, &options).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use futures_util::stream::{self, Stream, TryStreamExt};
+use serde_json::{Map, Value};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::csv;
+use crate::error::{Result, VeilMailError};
+use crate::pagination::Page;
+use crate::resources::audiences::{Subscriber, SubscriberFilter, Subscribers};
+
+/// Columns written by CSV exports when none are selected.
+pub const DEFAULT_CSV_COLUMNS: &[&str] = &[
+    "id",
+    "email",
+    "firstName",
+    "lastName",
+    "status",
+    "consentSource",
+    "consentTimestamp",
+    "engagementScore",
+    "createdAt",
+];
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Output encoding for [`Subscribers::export_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+    /// A single JSON array.
+    Json,
+}
+
+/// Settings for [`Subscribers::export_to`].
+#[derive(Debug, Clone)]
+pub struct SubscriberExport {
+    pub format: ExportFormat,
+    /// Fields to include, by their JSON name. Custom properties are selected
+    /// as `properties.<name>`. `None` writes every field for JSON formats and
+    /// [`DEFAULT_CSV_COLUMNS`] for CSV.
+    pub columns: Option<Vec<String>>,
+    pub filter: SubscriberFilter,
+}
+
+impl SubscriberExport {
+    pub fn new(format: ExportFormat) -> Self {
+        Self {
+            format,
+            columns: None,
+            filter: SubscriberFilter::default(),
+        }
+    }
+
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Export only subscribers matching `filter`. Its cursor is ignored.
+    pub fn filter(mut self, filter: SubscriberFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+impl<'a> Subscribers<'a> {
+    /// Every subscriber matching `filter`, following pagination cursors
+    /// lazily. `filter.limit` sets the page size.
+    pub fn stream(
+        &self,
+        filter: SubscriberFilter,
+    ) -> impl Stream<Item = Result<Subscriber>> + Send + 'a {
+        let http = self.http;
+        let path = self.base_path.clone();
+        let mut filter = filter;
+        filter.limit.get_or_insert(DEFAULT_PAGE_SIZE);
+
+        stream::try_unfold(Some(filter), move |filter| {
+            let path = path.clone();
+            async move {
+                let Some(mut filter) = filter else {
+                    return Ok::<_, VeilMailError>(None);
+                };
+
+                let query = filter.query();
+                let params: Vec<(&str, &str)> = query
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str()))
+                    .collect();
+                let page: Page<Subscriber> =
+                    serde_json::from_value(http.get(&path, Some(&params)).await?)?;
+
+                let next = page.next().map(|cursor| {
+                    filter.cursor = Some(cursor.to_string());
+                    filter
+                });
+                Ok(Some((stream::iter(page.data.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Write subscribers to `writer` as they are fetched, returning how many
+    /// were written.
+    pub async fn export_to<W>(&self, writer: &mut W, options: &SubscriberExport) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let columns: Option<Vec<String>> = match (&options.columns, options.format) {
+            (Some(columns), _) => Some(columns.clone()),
+            (None, ExportFormat::Csv) => {
+                Some(DEFAULT_CSV_COLUMNS.iter().map(|c| c.to_string()).collect())
+            }
+            (None, _) => None,
+        };
+
+        let mut filter = options.filter.clone();
+        filter.cursor = None;
+        let mut subscribers = Box::pin(self.stream(filter));
+
+        match options.format {
+            ExportFormat::Csv => {
+                let header: Vec<String> = columns.iter().flatten().map(|c| csv::field(c)).collect();
+                write(writer, format!("{}\n", header.join(",")).as_bytes()).await?;
+            }
+            ExportFormat::Json => write(writer, b"[").await?,
+            ExportFormat::Ndjson => {}
+        }
+
+        let mut count = 0u64;
+        while let Some(subscriber) = subscribers.try_next().await? {
+            let record = serde_json::to_value(&subscriber)?;
+            let line = match options.format {
+                ExportFormat::Csv => {
+                    let fields: Vec<String> = columns
+                        .iter()
+                        .flatten()
+                        .map(|c| csv::field(&cell(lookup(&record, c))))
+                        .collect();
+                    format!("{}\n", fields.join(","))
+                }
+                ExportFormat::Ndjson | ExportFormat::Json => {
+                    let object = match &columns {
+                        Some(columns) => select(&record, columns),
+                        None => record,
+                    };
+                    let separator = match (options.format, count) {
+                        (ExportFormat::Json, 0) => "\n",
+                        (ExportFormat::Json, _) => ",\n",
+                        _ => "",
+                    };
+                    let terminator = if options.format == ExportFormat::Ndjson {
+                        "\n"
+                    } else {
+                        ""
+                    };
+                    format!("{}{}{}", separator, object, terminator)
+                }
+            };
+            write(writer, line.as_bytes()).await?;
+            count += 1;
+        }
+
+        if options.format == ExportFormat::Json {
+            write(writer, if count == 0 { b"]\n" } else { b"\n]\n" }).await?;
+        }
+        writer.flush().await.map_err(write_error)?;
+        Ok(count)
+    }
+}
+
+/// Resolve `column`, looking inside `properties` for `properties.<name>`.
+fn lookup<'v>(record: &'v Value, column: &str) -> Option<&'v Value> {
+    match column.split_once('.') {
+        Some((parent, child)) => record.get(parent).and_then(|p| p.get(child)),
+        None => record.get(column),
+    }
+}
+
+fn select(record: &Value, columns: &[String]) -> Value {
+    let object: Map<String, Value> = columns
+        .iter()
+        .map(|c| (c.clone(), lookup(record, c).cloned().unwrap_or(Value::Null)))
+        .collect();
+    Value::Object(object)
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+async fn write<W: AsyncWrite + Unpin>(writer: &mut W, bytes: &[u8]) -> Result<()> {
+    writer.write_all(bytes).await.map_err(write_error)
+}
+
+fn write_error(e: std::io::Error) -> VeilMailError {
+    VeilMailError::Other(format!("writing export: {}", e))
+}
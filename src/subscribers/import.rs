@@ -0,0 +1,485 @@
+//! Streaming CSV import.
+//!
+//! Rows are read one at a time, validated and deduplicated locally, and
+//! uploaded through [`Subscribers::import`] in chunks, so the file itself is
+//! never held in memory. The report and the set of addresses kept for
+//! deduplication still grow with the number of rows.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use veilmail::subscribers::import::CsvImport;
+//!
+//! let file = tokio::fs::File::open("subscribers.csv").await.unwrap();
+//! let options = CsvImport::new()
+//!     .email_column("Email Address")
+//!     .property("Plan", "plan")
+//!     .chunk_size(2_000);
+//!
+//! let report = client
+//!     .audiences()
+//!     .subscribers("audience_xxxxx")
+//!     .import_csv(file, &options)
+//!     .await?;
+//!
+//! println!("{} accepted, {} duplicates", report.accepted, report.duplicates.len());
+//! for row in &report.rejected {
+//!     println!("line {}: {}", row.line, row.reason);
+//! }
+//! if let Some(stop) = &report.stopped {
+//!     eprintln!("import incomplete: {}", stop);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use csv_async::{AsyncReaderBuilder, StringRecord, Trim};
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use tokio::io::AsyncRead;
+
+use crate::error::{Result, VeilMailError};
+use crate::resources::audiences::Subscribers;
+
+/// Column mapping and upload settings for [`Subscribers::import_csv`].
+///
+/// Column names are matched ignoring case, spaces, and punctuation, so
+/// `email` matches `Email`, `E-mail`, and `EMAIL`.
+#[derive(Debug, Clone)]
+pub struct CsvImport {
+    email_column: String,
+    first_name_column: Option<String>,
+    last_name_column: Option<String>,
+    properties: Vec<(String, String)>,
+    extra_columns_as_properties: bool,
+    chunk_size: usize,
+    delimiter: u8,
+}
+
+impl Default for CsvImport {
+    fn default() -> Self {
+        Self {
+            email_column: "email".to_string(),
+            first_name_column: None,
+            last_name_column: None,
+            properties: Vec::new(),
+            extra_columns_as_properties: false,
+            chunk_size: 1000,
+            delimiter: b',',
+        }
+    }
+}
+
+impl CsvImport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Column holding the email address. Defaults to `email`.
+    pub fn email_column(mut self, column: impl Into<String>) -> Self {
+        self.email_column = column.into();
+        self
+    }
+
+    /// Column holding the first name. Defaults to `first_name` if present.
+    pub fn first_name_column(mut self, column: impl Into<String>) -> Self {
+        self.first_name_column = Some(column.into());
+        self
+    }
+
+    /// Column holding the last name. Defaults to `last_name` if present.
+    pub fn last_name_column(mut self, column: impl Into<String>) -> Self {
+        self.last_name_column = Some(column.into());
+        self
+    }
+
+    /// Store `column` as the custom property `property`.
+    pub fn property(mut self, column: impl Into<String>, property: impl Into<String>) -> Self {
+        self.properties.push((column.into(), property.into()));
+        self
+    }
+
+    /// Store every unmapped column as a custom property named after its
+    /// header.
+    pub fn extra_columns_as_properties(mut self, yes: bool) -> Self {
+        self.extra_columns_as_properties = yes;
+        self
+    }
+
+    /// Subscribers per upload request. Defaults to 1000.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Field delimiter. Defaults to `,`.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+}
+
+/// Why a row was not uploaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", content = "detail", rename_all = "snake_case")]
+pub enum RejectReason {
+    MissingEmail,
+    InvalidEmail,
+    /// The row could not be parsed, e.g. invalid UTF-8.
+    Malformed(String),
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::MissingEmail => f.write_str("missing email address"),
+            RejectReason::InvalidEmail => f.write_str("invalid email address"),
+            RejectReason::Malformed(detail) => write!(f, "malformed row: {}", detail),
+        }
+    }
+}
+
+/// A row that failed local validation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedRow {
+    /// 1-based line number in the file.
+    pub line: u64,
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub reason: RejectReason,
+}
+
+/// A row skipped because its address appeared earlier in the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateRow {
+    pub line: u64,
+    pub email: String,
+    /// Line of the row that was kept.
+    pub first_line: u64,
+}
+
+/// Why [`Subscribers::import_csv`] stopped before the end of the file.
+/// Rows after the given lines were not read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum ImportStop {
+    /// Uploading the rows on lines `first_line..=last_line` failed; none of
+    /// them were imported.
+    Upload {
+        first_line: u64,
+        last_line: u64,
+        rows: usize,
+        error: String,
+    },
+    /// Reading the file failed after `line`.
+    Read { line: u64, error: String },
+}
+
+impl fmt::Display for ImportStop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportStop::Upload {
+                first_line,
+                last_line,
+                rows,
+                error,
+            } => write!(
+                f,
+                "uploading {} row(s) on lines {}-{} failed: {}",
+                rows, first_line, last_line, error
+            ),
+            ImportStop::Read { line, error } => {
+                write!(f, "reading CSV failed after line {}: {}", line, error)
+            }
+        }
+    }
+}
+
+/// Outcome of [`Subscribers::import_csv`].
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Rows uploaded.
+    pub accepted: usize,
+    pub rejected: Vec<RejectedRow>,
+    pub duplicates: Vec<DuplicateRow>,
+    /// The API response for each uploaded chunk, in order.
+    pub jobs: Vec<Value>,
+    /// Set if the import stopped early; everything counted above still
+    /// happened.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped: Option<ImportStop>,
+}
+
+impl ImportReport {
+    /// Whether the whole file was read and every chunk uploaded.
+    pub fn is_complete(&self) -> bool {
+        self.stopped.is_none()
+    }
+}
+
+/// Valid rows waiting to be uploaded, with the lines they came from.
+struct Chunk {
+    subscribers: Vec<Value>,
+    first_line: u64,
+    last_line: u64,
+}
+
+/// Header positions resolved from a [`CsvImport`].
+struct Columns {
+    email: usize,
+    first_name: Option<usize>,
+    last_name: Option<usize>,
+    properties: Vec<(usize, String)>,
+}
+
+impl Columns {
+    fn resolve(headers: &StringRecord, options: &CsvImport) -> Result<Self> {
+        let find = |name: &str| {
+            let wanted = normalize(name);
+            headers.iter().position(|h| normalize(h) == wanted)
+        };
+        let required = |name: &str| {
+            find(name).ok_or_else(|| VeilMailError::Other(format!("CSV has no '{}' column", name)))
+        };
+
+        let email = required(&options.email_column)?;
+        let first_name = match &options.first_name_column {
+            Some(name) => Some(required(name)?),
+            None => find("first_name"),
+        };
+        let last_name = match &options.last_name_column {
+            Some(name) => Some(required(name)?),
+            None => find("last_name"),
+        };
+
+        let mut properties = Vec::new();
+        for (column, property) in &options.properties {
+            properties.push((required(column)?, property.clone()));
+        }
+        if options.extra_columns_as_properties {
+            for (index, header) in headers.iter().enumerate() {
+                let mapped = index == email
+                    || Some(index) == first_name
+                    || Some(index) == last_name
+                    || properties.iter().any(|(i, _)| *i == index);
+                if !mapped && !header.trim().is_empty() {
+                    properties.push((index, header.trim().to_string()));
+                }
+            }
+        }
+
+        Ok(Self {
+            email,
+            first_name,
+            last_name,
+            properties,
+        })
+    }
+
+    fn subscriber(&self, record: &StringRecord, email: &str) -> Value {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let mut subscriber = Map::new();
+        subscriber.insert("email".into(), json!(email));
+        if let Some(first_name) = field(self.first_name) {
+            subscriber.insert("firstName".into(), json!(first_name));
+        }
+        if let Some(last_name) = field(self.last_name) {
+            subscriber.insert("lastName".into(), json!(last_name));
+        }
+
+        let properties: Map<String, Value> = self
+            .properties
+            .iter()
+            .filter_map(|(index, name)| field(Some(*index)).map(|v| (name.clone(), json!(v))))
+            .collect();
+        if !properties.is_empty() {
+            subscriber.insert("properties".into(), Value::Object(properties));
+        }
+
+        Value::Object(subscriber)
+    }
+}
+
+impl Subscribers<'_> {
+    /// Import subscribers from CSV with a header row.
+    ///
+    /// Rows with a missing or invalid address are reported and skipped, as
+    /// are repeats of an address seen earlier in the file (compared
+    /// case-insensitively).
+    ///
+    /// Stops at the first failed upload or read error and returns the report
+    /// so far with [`ImportReport::stopped`] set; chunks sent before it stay
+    /// imported. Only a missing header row or column is returned as `Err`.
+    pub async fn import_csv<R>(&self, reader: R, options: &CsvImport) -> Result<ImportReport>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let mut csv = AsyncReaderBuilder::new()
+            .delimiter(options.delimiter)
+            .flexible(true)
+            .trim(Trim::All)
+            .create_reader(reader);
+
+        let headers = csv.headers().await.map_err(csv_error)?.clone();
+        let columns = Columns::resolve(&headers, options)?;
+
+        let mut report = ImportReport::default();
+        let mut seen: HashMap<String, u64> = HashMap::new();
+        let mut chunk = Chunk {
+            subscribers: Vec::with_capacity(options.chunk_size),
+            first_line: 0,
+            last_line: 0,
+        };
+        let mut last_line = 1;
+
+        let mut records = csv.records();
+        while let Some(record) = records.next().await {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) if e.is_io_error() => {
+                    report.stopped = Some(ImportStop::Read {
+                        line: last_line,
+                        error: e.to_string(),
+                    });
+                    return Ok(report);
+                }
+                Err(e) => {
+                    report.rejected.push(RejectedRow {
+                        line: e.position().map_or(0, |p| p.line()),
+                        email: None,
+                        reason: RejectReason::Malformed(e.to_string()),
+                    });
+                    continue;
+                }
+            };
+            let line = record.position().map_or(0, |p| p.line());
+            last_line = line;
+
+            let email = record.get(columns.email).unwrap_or("");
+            if email.is_empty() {
+                report.rejected.push(RejectedRow {
+                    line,
+                    email: None,
+                    reason: RejectReason::MissingEmail,
+                });
+                continue;
+            }
+            if !is_valid_email(email) {
+                report.rejected.push(RejectedRow {
+                    line,
+                    email: Some(email.to_string()),
+                    reason: RejectReason::InvalidEmail,
+                });
+                continue;
+            }
+            if let Some(&first_line) = seen.get(&email.to_lowercase()) {
+                report.duplicates.push(DuplicateRow {
+                    line,
+                    email: email.to_string(),
+                    first_line,
+                });
+                continue;
+            }
+            seen.insert(email.to_lowercase(), line);
+
+            if chunk.subscribers.is_empty() {
+                chunk.first_line = line;
+            }
+            chunk.last_line = line;
+            chunk.subscribers.push(columns.subscriber(&record, email));
+            if chunk.subscribers.len() >= options.chunk_size
+                && !self.upload(&mut chunk, &mut report).await
+            {
+                return Ok(report);
+            }
+        }
+        if !chunk.subscribers.is_empty() {
+            self.upload(&mut chunk, &mut report).await;
+        }
+
+        Ok(report)
+    }
+
+    /// Upload and empty `chunk`, recording a failure in the report. Returns
+    /// whether the upload succeeded.
+    async fn upload(&self, chunk: &mut Chunk, report: &mut ImportReport) -> bool {
+        let rows = chunk.subscribers.len();
+        let body = json!({ "subscribers": std::mem::take(&mut chunk.subscribers) });
+        match self.import(body).await {
+            Ok(job) => {
+                report.jobs.push(job);
+                report.accepted += rows;
+                true
+            }
+            Err(e) => {
+                report.stopped = Some(ImportStop::Upload {
+                    first_line: chunk.first_line,
+                    last_line: chunk.last_line,
+                    rows,
+                    error: e.to_string(),
+                });
+                false
+            }
+        }
+    }
+}
+
+/// A structural check for `local@domain.tld`; deliverability is up to the
+/// server.
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return false;
+    };
+    if email.len() > 254 || local.is_empty() || local.len() > 64 {
+        return false;
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return false;
+    }
+    if local
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '@' | '<' | '>' | ',' | ';'))
+    {
+        return false;
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    labels.len() >= 2
+        && labels.last().is_some_and(|tld| tld.len() >= 2)
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+fn normalize(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn csv_error(e: csv_async::Error) -> VeilMailError {
+    VeilMailError::Other(format!("reading CSV: {}", e))
+}
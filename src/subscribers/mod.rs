@@ -0,0 +1,5 @@
+//! Bulk subscriber transfer.
+
+pub mod export;
+#[cfg(feature = "csv")]
+pub mod import;
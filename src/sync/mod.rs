@@ -0,0 +1,4 @@
+//! Converge server-side resources with definitions kept in version control.
+
+pub mod sequences;
+pub mod templates;
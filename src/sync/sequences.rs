@@ -0,0 +1,705 @@
+//! Sync a sequence's steps from a YAML or TOML definition.
+//!
+//! ```yaml
+//! id: seq_xxxxx
+//! name: Onboarding
+//! steps:
+//!   - email: welcome            # template name
+//!   - delay: 2d                 # 30m, 4h, 2d, 1w
+//!   - condition: opened_previous
+//!   - template_id: tmpl_xxxxx   # or a template ID
+//!     subject: Getting started
+//!   - delay: 1w
+//!   - condition: { property: plan, equals: free }
+//!   - email: upgrade
+//! ```
+//!
+//! The steps of the live sequence are matched against the definition and the
+//! fewest `add_step`, `update_step`, `delete_step`, and `reorder_steps` calls
+//! needed to converge are planned. The sequence's name and trigger are not
+//! changed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use veilmail::sync::sequences;
+//!
+//! # #[cfg(feature = "yaml")] {
+//! let definition = sequences::load_file("sequences/onboarding.yaml")?;
+//! let plan = sequences::plan(&client.sequences(), &client.templates(), &definition).await?;
+//! print!("{}", plan);
+//!
+//! sequences::apply(&client.sequences(), &plan).await?;
+//! # }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(any(feature = "yaml", feature = "toml"))]
+use std::path::Path;
+
+use crate::error::{Result, VeilMailError};
+use crate::resources::sequences::{
+    validate_steps, Condition, DelayUnit, SequenceStep, Sequences, StepAction,
+};
+use crate::resources::templates::Templates;
+
+/// A sequence as written in a definition file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SequenceDefinition {
+    /// ID of the live sequence to converge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: String,
+    pub steps: Vec<StepDefinition>,
+}
+
+/// One step of a [`SequenceDefinition`]. Exactly one of `email`,
+/// `template_id`, `delay`, or `condition` must be set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct StepDefinition {
+    /// Template name, resolved to an ID when planning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// Subject override for an email step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// A number and unit: `30m`, `4h`, `2d`, or `1w`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<ConditionDefinition>,
+}
+
+/// `opened_previous`, `clicked_previous`, or `{ property, equals }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConditionDefinition {
+    Check(String),
+    Property { property: String, equals: Value },
+}
+
+impl SequenceDefinition {
+    /// Turn the definition into API steps, looking template names up in
+    /// `template_ids`, and check them with [`validate_steps`].
+    pub fn resolve(&self, template_ids: &HashMap<String, String>) -> Result<Vec<SequenceStep>> {
+        let steps = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                step.resolve(template_ids)
+                    .map_err(|e| definition_error(&format!("steps[{}]: {}", i, e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        validate_steps(&steps)?;
+        Ok(steps)
+    }
+
+    fn template_names(&self) -> impl Iterator<Item = &str> {
+        self.steps.iter().filter_map(|s| s.email.as_deref())
+    }
+}
+
+impl StepDefinition {
+    fn resolve(
+        &self,
+        template_ids: &HashMap<String, String>,
+    ) -> std::result::Result<SequenceStep, String> {
+        let set = [
+            self.email.is_some(),
+            self.template_id.is_some(),
+            self.delay.is_some(),
+            self.condition.is_some(),
+        ];
+        if set.iter().filter(|s| **s).count() != 1 {
+            return Err("set exactly one of email, template_id, delay, or condition".to_string());
+        }
+        if self.subject.is_some() && self.email.is_none() && self.template_id.is_none() {
+            return Err("subject only applies to email steps".to_string());
+        }
+
+        if let Some(name) = &self.email {
+            let template_id = template_ids
+                .get(name)
+                .ok_or_else(|| format!("no template named '{}'", name))?;
+            return Ok(email_step(template_id, &self.subject));
+        }
+        if let Some(template_id) = &self.template_id {
+            return Ok(email_step(template_id, &self.subject));
+        }
+        if let Some(delay) = &self.delay {
+            let (amount, unit) = parse_delay(delay)?;
+            return Ok(SequenceStep::delay(amount, unit));
+        }
+        let condition = match self.condition.as_ref().expect("checked above") {
+            ConditionDefinition::Check(check) => match check.as_str() {
+                "opened_previous" => Condition::OpenedPrevious,
+                "clicked_previous" => Condition::ClickedPrevious,
+                other => return Err(format!("unknown condition '{}'", other)),
+            },
+            ConditionDefinition::Property { property, equals } => Condition::PropertyEquals {
+                property: property.clone(),
+                value: equals.clone(),
+            },
+        };
+        Ok(SequenceStep::condition(condition))
+    }
+}
+
+fn email_step(template_id: &str, subject: &Option<String>) -> SequenceStep {
+    let mut step = SequenceStep::email(template_id);
+    if let StepAction::Email { subject: s, .. } = &mut step.action {
+        s.clone_from(subject);
+    }
+    step
+}
+
+/// Parse `30m`, `4h`, `2d`, `1w`, or the long unit names, e.g. `2 days`.
+fn parse_delay(delay: &str) -> std::result::Result<(u32, DelayUnit), String> {
+    let delay = delay.trim();
+    let split = delay
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(delay.len());
+    let (amount, unit) = delay.split_at(split);
+    let amount: u32 = amount
+        .parse()
+        .map_err(|_| format!("delay '{}' does not start with a number", delay))?;
+    let unit = match unit.trim() {
+        "m" | "min" | "minute" | "minutes" => DelayUnit::Minutes,
+        "h" | "hour" | "hours" => DelayUnit::Hours,
+        "d" | "day" | "days" => DelayUnit::Days,
+        "w" | "week" | "weeks" => DelayUnit::Weeks,
+        other => return Err(format!("unknown delay unit '{}'", other)),
+    };
+    Ok((amount, unit))
+}
+
+/// Read a definition from a `.yaml`/`.yml` or `.toml` file.
+#[cfg(any(feature = "yaml", feature = "toml"))]
+pub fn load_file(path: impl AsRef<Path>) -> Result<SequenceDefinition> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| VeilMailError::Other(format!("{}: {}", path.display(), e)))?;
+    let parsed = match path.extension().and_then(|e| e.to_str()) {
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => from_yaml(&contents),
+        #[cfg(feature = "toml")]
+        Some("toml") => from_toml(&contents),
+        _ => Err(definition_error("unsupported file extension")),
+    };
+    parsed.map_err(|e| match e {
+        VeilMailError::Other(message) => {
+            VeilMailError::Other(format!("{}: {}", path.display(), message))
+        }
+        e => e,
+    })
+}
+
+#[cfg(feature = "yaml")]
+pub fn from_yaml(source: &str) -> Result<SequenceDefinition> {
+    serde_norway::from_str(source).map_err(|e| definition_error(&e.to_string()))
+}
+
+#[cfg(feature = "toml")]
+pub fn from_toml(source: &str) -> Result<SequenceDefinition> {
+    toml::from_str(source).map_err(|e| definition_error(&e.to_string()))
+}
+
+/// Where a step ends up, for [`StepChange::Reorder`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum StepRef {
+    /// An existing step, by ID.
+    Existing(String),
+    /// The step added by the [`StepChange::Add`] at this index in the
+    /// definition.
+    New(usize),
+}
+
+impl fmt::Display for StepRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepRef::Existing(id) => f.write_str(id),
+            StepRef::New(index) => write!(f, "(new #{})", index + 1),
+        }
+    }
+}
+
+/// One call needed to converge.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(
+    tag = "action",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum StepChange {
+    /// Append a step; `index` is its 0-based place in the definition.
+    Add {
+        index: usize,
+        step: SequenceStep,
+    },
+    /// Replace an existing step in place.
+    Update {
+        step_id: String,
+        index: usize,
+        from: StepAction,
+        step: SequenceStep,
+    },
+    Delete {
+        step_id: String,
+        step: StepAction,
+    },
+    /// Put all steps in definition order.
+    Reorder {
+        order: Vec<StepRef>,
+    },
+}
+
+/// The calls [`apply`] would make. `Display` renders a dry-run summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencePlan {
+    pub sequence_id: String,
+    pub changes: Vec<StepChange>,
+    /// Steps already matching, left alone.
+    pub unchanged: usize,
+}
+
+impl SequencePlan {
+    /// Whether the live sequence already matches.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn count(&self, f: impl Fn(&StepChange) -> bool) -> usize {
+        self.changes.iter().filter(|c| f(c)).count()
+    }
+}
+
+impl fmt::Display for SequencePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            match change {
+                StepChange::Add { index, step } => {
+                    writeln!(f, "+ add step #{}: {}", index + 1, step.action)?
+                }
+                StepChange::Update {
+                    index, from, step, ..
+                } => writeln!(
+                    f,
+                    "~ update step #{}: {} -> {}",
+                    index + 1,
+                    from,
+                    step.action
+                )?,
+                StepChange::Delete { step_id, step } => {
+                    writeln!(f, "- delete step {}: {}", step_id, step)?
+                }
+                StepChange::Reorder { order } => {
+                    let order: Vec<String> = order.iter().map(ToString::to_string).collect();
+                    writeln!(f, "= reorder: {}", order.join(", "))?
+                }
+            }
+        }
+        writeln!(
+            f,
+            "Plan for {}: {} to add, {} to update, {} to delete, {} unchanged{}.",
+            self.sequence_id,
+            self.count(|c| matches!(c, StepChange::Add { .. })),
+            self.count(|c| matches!(c, StepChange::Update { .. })),
+            self.count(|c| matches!(c, StepChange::Delete { .. })),
+            self.unchanged,
+            if self.count(|c| matches!(c, StepChange::Reorder { .. })) > 0 {
+                ", then reorder"
+            } else {
+                ""
+            },
+        )
+    }
+}
+
+/// What [`apply`] did.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SequenceSyncReport {
+    pub added: Vec<SequenceStep>,
+    pub updated: Vec<SequenceStep>,
+    /// IDs of deleted steps.
+    pub deleted: Vec<String>,
+    pub reordered: bool,
+}
+
+/// Fetch the live sequence (and templates, if the definition names any)
+/// and compute the calls needed to match `definition`.
+pub async fn plan(
+    sequences: &Sequences<'_>,
+    templates: &Templates<'_>,
+    definition: &SequenceDefinition,
+) -> Result<SequencePlan> {
+    let id = definition
+        .id
+        .as_deref()
+        .ok_or_else(|| definition_error("definition has no sequence id"))?;
+
+    let mut template_ids = HashMap::new();
+    if definition.template_names().next().is_some() {
+        for template in templates.list_all().await? {
+            if template_ids
+                .insert(template.name.clone(), template.id.clone())
+                .is_some()
+            {
+                return Err(definition_error(&format!(
+                    "more than one remote template is named '{}'",
+                    template.name
+                )));
+            }
+        }
+    }
+
+    let local = definition.resolve(&template_ids)?;
+    let remote = sequences.get(id).await?;
+    diff(id, local, &remote.steps)
+}
+
+/// Compute the calls needed to turn `remote` into `local`.
+///
+/// Identical steps are kept, even if they moved. Remaining local steps
+/// reuse a leftover remote step of the same kind through an update, and
+/// anything else is added or deleted. A reorder is planned only if the
+/// result would be out of order.
+///
+/// Fails if a remote step has no ID, since it could be neither kept nor
+/// deleted.
+pub fn diff(
+    sequence_id: &str,
+    local: Vec<SequenceStep>,
+    remote: &[SequenceStep],
+) -> Result<SequencePlan> {
+    if let Some(i) = remote.iter().position(|s| s.id.is_none()) {
+        return Err(VeilMailError::Other(format!(
+            "sequence {}: remote step #{} ({}) has no ID",
+            sequence_id,
+            i + 1,
+            remote[i].action
+        )));
+    }
+    let mut remote: Vec<&SequenceStep> = remote.iter().collect();
+    remote.sort_by_key(|s| s.position.unwrap_or(u32::MAX));
+
+    let mut matched: Vec<Option<usize>> = vec![None; local.len()];
+    let mut used = vec![false; remote.len()];
+
+    // Identical steps first, so they are never rewritten.
+    for (i, step) in local.iter().enumerate() {
+        if let Some(j) = (0..remote.len()).find(|&j| !used[j] && remote[j].action == step.action) {
+            matched[i] = Some(j);
+            used[j] = true;
+        }
+    }
+
+    let mut plan = SequencePlan {
+        sequence_id: sequence_id.to_string(),
+        unchanged: matched.iter().flatten().count(),
+        ..SequencePlan::default()
+    };
+
+    // Then same-kind steps, updated in place.
+    let mut updates = Vec::new();
+    for (i, step) in local.iter().enumerate() {
+        if matched[i].is_some() {
+            continue;
+        }
+        let kind = std::mem::discriminant(&step.action);
+        if let Some(j) = (0..remote.len())
+            .find(|&j| !used[j] && std::mem::discriminant(&remote[j].action) == kind)
+        {
+            matched[i] = Some(j);
+            used[j] = true;
+            updates.push(StepChange::Update {
+                step_id: remote_id(remote[j]),
+                index: i,
+                from: remote[j].action.clone(),
+                step: bare(step),
+            });
+        }
+    }
+
+    for (j, step) in remote.iter().enumerate() {
+        if !used[j] {
+            plan.changes.push(StepChange::Delete {
+                step_id: remote_id(step),
+                step: step.action.clone(),
+            });
+        }
+    }
+    plan.changes.extend(updates);
+    for (i, step) in local.iter().enumerate() {
+        if matched[i].is_none() {
+            plan.changes.push(StepChange::Add {
+                index: i,
+                step: bare(step),
+            });
+        }
+    }
+
+    // Kept steps stay in their old relative order and added ones go last.
+    let desired: Vec<StepRef> = matched
+        .iter()
+        .enumerate()
+        .map(|(i, m)| match m {
+            Some(j) => StepRef::Existing(remote_id(remote[*j])),
+            None => StepRef::New(i),
+        })
+        .collect();
+    let mut kept: Vec<(usize, usize)> = matched
+        .iter()
+        .enumerate()
+        .filter_map(|(i, m)| m.map(|j| (j, i)))
+        .collect();
+    kept.sort();
+    let resulting: Vec<StepRef> = kept
+        .into_iter()
+        .map(|(j, _)| StepRef::Existing(remote_id(remote[j])))
+        .chain(
+            matched
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.is_none())
+                .map(|(i, _)| StepRef::New(i)),
+        )
+        .collect();
+    if resulting != desired {
+        plan.changes.push(StepChange::Reorder { order: desired });
+    }
+
+    Ok(plan)
+}
+
+/// Execute a plan in order, stopping at the first failed request.
+pub async fn apply(sequences: &Sequences<'_>, plan: &SequencePlan) -> Result<SequenceSyncReport> {
+    let id = plan.sequence_id.as_str();
+    let mut report = SequenceSyncReport::default();
+    let mut new_ids: HashMap<usize, String> = HashMap::new();
+
+    for change in &plan.changes {
+        match change {
+            StepChange::Delete { step_id, .. } => {
+                sequences.delete_step(id, step_id).await?;
+                report.deleted.push(step_id.clone());
+            }
+            StepChange::Update { step_id, step, .. } => {
+                report
+                    .updated
+                    .push(sequences.update_step(id, step_id, step).await?);
+            }
+            StepChange::Add { index, step } => {
+                let added = sequences.add_step(id, step).await?;
+                if let Some(step_id) = &added.id {
+                    new_ids.insert(*index, step_id.clone());
+                }
+                report.added.push(added);
+            }
+            StepChange::Reorder { order } => {
+                let step_ids = order
+                    .iter()
+                    .map(|r| match r {
+                        StepRef::Existing(step_id) => Ok(step_id.clone()),
+                        StepRef::New(index) => new_ids.get(index).cloned().ok_or_else(|| {
+                            VeilMailError::Other(format!(
+                                "no ID was returned for new step #{}",
+                                index + 1
+                            ))
+                        }),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                sequences.reorder_steps(id, &step_ids).await?;
+                report.reordered = true;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn bare(step: &SequenceStep) -> SequenceStep {
+    SequenceStep {
+        id: None,
+        position: None,
+        action: step.action.clone(),
+    }
+}
+
+fn remote_id(step: &SequenceStep) -> String {
+    step.id.clone().unwrap_or_default()
+}
+
+fn definition_error(message: &str) -> VeilMailError {
+    VeilMailError::Other(format!("sequence definition: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(template_id: &str) -> SequenceStep {
+        SequenceStep::email(template_id)
+    }
+
+    fn delay(days: u32) -> SequenceStep {
+        SequenceStep::delay(days, DelayUnit::Days)
+    }
+
+    /// `steps` as the API returns them, with IDs and positions.
+    fn remote(steps: &[(&str, SequenceStep)]) -> Vec<SequenceStep> {
+        steps
+            .iter()
+            .enumerate()
+            .map(|(position, (id, step))| SequenceStep {
+                id: Some(id.to_string()),
+                position: Some(position as u32),
+                ..step.clone()
+            })
+            .collect()
+    }
+
+    fn existing(id: &str) -> StepRef {
+        StepRef::Existing(id.to_string())
+    }
+
+    #[test]
+    fn identical_steps_need_no_changes() {
+        let local = vec![email("tmpl_a"), delay(2), email("tmpl_b")];
+        let remote = remote(&[
+            ("step_1", email("tmpl_a")),
+            ("step_2", delay(2)),
+            ("step_3", email("tmpl_b")),
+        ]);
+        let plan = diff("seq_1", local, &remote).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.unchanged, 3);
+    }
+
+    #[test]
+    fn remote_order_follows_position_not_list_order() {
+        let mut remote = remote(&[("step_1", email("tmpl_a")), ("step_2", email("tmpl_b"))]);
+        remote.reverse();
+        let plan = diff("seq_1", vec![email("tmpl_a"), email("tmpl_b")], &remote).unwrap();
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn swapped_steps_only_need_a_reorder() {
+        let local = vec![email("tmpl_b"), delay(1), email("tmpl_a")];
+        let remote = remote(&[
+            ("step_1", email("tmpl_a")),
+            ("step_2", delay(1)),
+            ("step_3", email("tmpl_b")),
+        ]);
+        let plan = diff("seq_1", local, &remote).unwrap();
+        assert_eq!(
+            plan.changes,
+            [StepChange::Reorder {
+                order: vec![existing("step_3"), existing("step_2"), existing("step_1")],
+            }]
+        );
+        assert_eq!(plan.unchanged, 3);
+    }
+
+    #[test]
+    fn same_kind_steps_are_updated_in_place() {
+        let local = vec![email("tmpl_a"), delay(3), email("tmpl_c")];
+        let remote = remote(&[
+            ("step_1", email("tmpl_a")),
+            ("step_2", delay(2)),
+            ("step_3", email("tmpl_b")),
+        ]);
+        let plan = diff("seq_1", local, &remote).unwrap();
+        assert_eq!(
+            plan.changes,
+            [
+                StepChange::Update {
+                    step_id: "step_2".to_string(),
+                    index: 1,
+                    from: delay(2).action,
+                    step: delay(3),
+                },
+                StepChange::Update {
+                    step_id: "step_3".to_string(),
+                    index: 2,
+                    from: email("tmpl_b").action,
+                    step: email("tmpl_c"),
+                },
+            ]
+        );
+        assert_eq!(plan.unchanged, 1);
+    }
+
+    #[test]
+    fn other_kinds_are_added_and_deleted() {
+        let condition = SequenceStep::condition(Condition::PropertyEquals {
+            property: "plan".to_string(),
+            value: Value::from("free"),
+        });
+        let local = vec![email("tmpl_a"), condition.clone(), email("tmpl_b")];
+        let remote = remote(&[("step_1", email("tmpl_a")), ("step_2", delay(1))]);
+        let plan = diff("seq_1", local, &remote).unwrap();
+        assert_eq!(
+            plan.changes,
+            [
+                StepChange::Delete {
+                    step_id: "step_2".to_string(),
+                    step: delay(1).action,
+                },
+                StepChange::Add {
+                    index: 1,
+                    step: condition,
+                },
+                StepChange::Add {
+                    index: 2,
+                    step: email("tmpl_b"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn added_step_before_existing_ones_is_reordered() {
+        let local = vec![email("tmpl_a"), delay(1), email("tmpl_b")];
+        let remote = remote(&[("step_1", delay(1)), ("step_2", email("tmpl_b"))]);
+        let plan = diff("seq_1", local, &remote).unwrap();
+        assert_eq!(
+            plan.changes,
+            [
+                StepChange::Add {
+                    index: 0,
+                    step: email("tmpl_a"),
+                },
+                StepChange::Reorder {
+                    order: vec![StepRef::New(0), existing("step_1"), existing("step_2")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn remote_steps_without_an_id_are_rejected() {
+        let mut remote = remote(&[("step_1", email("tmpl_a")), ("step_2", email("tmpl_b"))]);
+        remote[1].id = None;
+        match diff("seq_1", vec![email("tmpl_a"), email("tmpl_b")], &remote) {
+            Err(VeilMailError::Other(message)) => {
+                assert!(message.contains("remote step #2"), "{}", message)
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Sync templates from a local directory.
+//!
+//! Each template is a group of files sharing a stem:
+//!
+//! ```text
+//! templates/
+//!   welcome.html   # HTML body
+//!   welcome.txt    # plain-text body
+//!   welcome.json   # {"subject": "...", "variables": [...]}, optional
+//! ```
+//!
+//! The stem is the template name unless the `.json` file sets `"name"`.
+//! Local and remote templates are matched by name.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run(client: veilmail::VeilMail) -> veilmail::error::Result<()> {
+//! use veilmail::sync::templates::{self, SyncOptions};
+//!
+//! let local = templates::load_dir("templates")?;
+//! let plan = templates::plan(&client.templates(), local, SyncOptions::default()).await?;
+//! print!("{}", plan);
+//!
+//! let report = templates::apply(&client.templates(), &plan).await?;
+//! println!("{} created, {} updated", report.created.len(), report.updated.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use crate::error::{Result, VeilMailError};
+use crate::render::TemplateSource;
+use crate::resources::templates::{
+    deserialize_variables, Template, TemplateParams, TemplateVariable, Templates,
+};
+
+/// Settings for [`plan`] and [`diff`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncOptions {
+    /// Delete remote templates that have no local counterpart.
+    pub prune: bool,
+}
+
+impl SyncOptions {
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+}
+
+/// One operation needed to converge.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TemplateChange {
+    Create {
+        params: TemplateParams,
+    },
+    Update {
+        id: String,
+        params: TemplateParams,
+        /// Fields that differ, e.g. `["html", "subject"]`.
+        fields: Vec<&'static str>,
+    },
+    Delete {
+        id: String,
+        name: String,
+    },
+}
+
+impl TemplateChange {
+    pub fn name(&self) -> &str {
+        match self {
+            TemplateChange::Create { params } | TemplateChange::Update { params, .. } => {
+                &params.name
+            }
+            TemplateChange::Delete { name, .. } => name,
+        }
+    }
+}
+
+/// The changes [`apply`] would make. `Display` renders a dry-run summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncPlan {
+    pub changes: Vec<TemplateChange>,
+    /// Names of templates already in sync.
+    pub unchanged: Vec<String>,
+}
+
+impl SyncPlan {
+    /// Whether local and remote templates already match.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn count(&self, f: impl Fn(&TemplateChange) -> bool) -> usize {
+        self.changes.iter().filter(|c| f(c)).count()
+    }
+}
+
+impl fmt::Display for SyncPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            match change {
+                TemplateChange::Create { params } => writeln!(f, "+ create {}", params.name)?,
+                TemplateChange::Update { params, fields, .. } => {
+                    writeln!(f, "~ update {} ({})", params.name, fields.join(", "))?
+                }
+                TemplateChange::Delete { name, .. } => writeln!(f, "- delete {}", name)?,
+            }
+        }
+        writeln!(
+            f,
+            "Plan: {} to create, {} to update, {} to delete, {} unchanged.",
+            self.count(|c| matches!(c, TemplateChange::Create { .. })),
+            self.count(|c| matches!(c, TemplateChange::Update { .. })),
+            self.count(|c| matches!(c, TemplateChange::Delete { .. })),
+            self.unchanged.len(),
+        )
+    }
+}
+
+/// What [`apply`] did.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub created: Vec<Template>,
+    pub updated: Vec<Template>,
+    /// IDs of deleted templates.
+    pub deleted: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    name: Option<String>,
+    subject: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_variables")]
+    variables: Vec<TemplateVariable>,
+}
+
+/// Read the templates in `dir`, sorted by name.
+///
+/// Fails on unreadable files, invalid `.json` metadata, templates with no
+/// `.html` or `.txt` body, duplicate names, and template syntax errors.
+pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<TemplateParams>> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|e| io_error(dir, e))?;
+
+    let mut stems: BTreeMap<String, TemplateParams> = BTreeMap::new();
+    for entry in entries {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        let (Some(stem), Some(ext)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|s| s.to_str()),
+        ) else {
+            continue;
+        };
+        if !matches!(ext, "html" | "txt" | "json") || !path.is_file() {
+            continue;
+        }
+
+        let contents = std::fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+        let params = stems
+            .entry(stem.to_string())
+            .or_insert_with(|| TemplateParams::new(stem));
+        match ext {
+            "html" => params.html = Some(contents),
+            "txt" => params.text = Some(contents),
+            _ => {
+                let metadata: Metadata = serde_json::from_str(&contents)
+                    .map_err(|e| VeilMailError::Other(format!("{}: {}", path.display(), e)))?;
+                if let Some(name) = metadata.name {
+                    params.name = name;
+                }
+                params.subject = metadata.subject;
+                params.variables = metadata.variables;
+            }
+        }
+    }
+
+    let mut templates: Vec<TemplateParams> = stems.into_values().collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for pair in templates.windows(2) {
+        if pair[0].name == pair[1].name {
+            return Err(VeilMailError::Other(format!(
+                "{}: more than one template is named '{}'",
+                dir.display(),
+                pair[0].name
+            )));
+        }
+    }
+    for params in &templates {
+        if params.html.is_none() && params.text.is_none() {
+            return Err(VeilMailError::Other(format!(
+                "{}: template '{}' has no .html or .txt body",
+                dir.display(),
+                params.name
+            )));
+        }
+        for part in [&params.subject, &params.html, &params.text]
+            .into_iter()
+            .flatten()
+        {
+            TemplateSource::parse(part).map_err(|e| match e {
+                VeilMailError::Template(message) => {
+                    VeilMailError::Template(format!("{}: {}", params.name, message))
+                }
+                e => e,
+            })?;
+        }
+    }
+
+    Ok(templates)
+}
+
+/// Fetch the remote templates and compute the changes needed to match
+/// `local`.
+pub async fn plan(
+    templates: &Templates<'_>,
+    local: Vec<TemplateParams>,
+    options: SyncOptions,
+) -> Result<SyncPlan> {
+    let remote = templates.list_all().await?;
+    diff(local, &remote, options)
+}
+
+/// Compute the changes needed to turn `remote` into `local`.
+///
+/// Fails if two remote templates share a name, since either could be meant.
+pub fn diff(
+    local: Vec<TemplateParams>,
+    remote: &[Template],
+    options: SyncOptions,
+) -> Result<SyncPlan> {
+    let mut by_name: HashMap<&str, &Template> = HashMap::new();
+    for template in remote {
+        if by_name.insert(&template.name, template).is_some() {
+            return Err(VeilMailError::Other(format!(
+                "more than one remote template is named '{}'",
+                template.name
+            )));
+        }
+    }
+
+    let mut plan = SyncPlan::default();
+    for params in local {
+        match by_name.remove(params.name.as_str()) {
+            None => plan.changes.push(TemplateChange::Create { params }),
+            Some(existing) => {
+                let fields = changed_fields(&params, existing);
+                if fields.is_empty() {
+                    plan.unchanged.push(params.name);
+                } else {
+                    plan.changes.push(TemplateChange::Update {
+                        id: existing.id.clone(),
+                        params,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    if options.prune {
+        let mut orphans: Vec<&Template> = by_name.into_values().collect();
+        orphans.sort_by(|a, b| a.name.cmp(&b.name));
+        for template in orphans {
+            plan.changes.push(TemplateChange::Delete {
+                id: template.id.clone(),
+                name: template.name.clone(),
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Execute a plan, stopping at the first failed request.
+pub async fn apply(templates: &Templates<'_>, plan: &SyncPlan) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+
+    for change in &plan.changes {
+        match change {
+            TemplateChange::Create { params } => {
+                report.created.push(templates.create(params).await?);
+            }
+            TemplateChange::Update { id, params, fields } => {
+                let body = update_body(params, fields)?;
+                report.updated.push(templates.update(id, body).await?);
+            }
+            TemplateChange::Delete { id, .. } => {
+                templates.delete(id).await?;
+                report.deleted.push(id.clone());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn changed_fields(local: &TemplateParams, remote: &Template) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if local.subject != remote.subject {
+        fields.push("subject");
+    }
+    if local.html != remote.html {
+        fields.push("html");
+    }
+    if local.text != remote.text {
+        fields.push("text");
+    }
+    if local.variables != remote.variables {
+        fields.push("variables");
+    }
+    fields
+}
+
+/// A PATCH body with only the changed fields, using `null` to clear them.
+fn update_body(params: &TemplateParams, fields: &[&str]) -> Result<Value> {
+    let full = serde_json::to_value(params)?;
+    let mut body = Map::new();
+    for &field in fields {
+        let value = match field {
+            "variables" => serde_json::to_value(&params.variables)?,
+            _ => full.get(field).cloned().unwrap_or(Value::Null),
+        };
+        body.insert(field.to_string(), value);
+    }
+    Ok(Value::Object(body))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> VeilMailError {
+    VeilMailError::Other(format!("{}: {}", path.display(), e))
+}
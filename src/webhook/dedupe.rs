@@ -0,0 +1,184 @@
+use futures_util::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+
+/// Remembers which webhook events have already been processed.
+///
+/// Veil Mail may deliver an event more than once, so handlers should process
+/// each event ID only once. [`WebhookRouter::dedupe`](super::WebhookRouter::dedupe)
+/// consults a store before dispatching; stores can also be used directly.
+///
+/// Implement this trait to keep the record in a shared database or cache
+/// when several processes receive webhooks.
+pub trait DedupeStore: Send + Sync {
+    /// Atomically record an event ID.
+    ///
+    /// Returns `true` if the ID had not been seen before (the caller should
+    /// process the event) and `false` if it is a duplicate.
+    fn claim<'a>(&'a self, event_id: &'a str) -> BoxFuture<'a, Result<bool>>;
+
+    /// Forget an event ID so that a redelivery is processed again.
+    ///
+    /// Called when handling a claimed event fails.
+    fn release<'a>(&'a self, event_id: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+/// An in-process [`DedupeStore`] that forgets event IDs after a TTL.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use veilmail::webhook::{DedupeStore, MemoryDedupeStore};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), veilmail::error::VeilMailError> {
+/// let store = MemoryDedupeStore::new(Duration::from_secs(24 * 60 * 60));
+/// assert!(store.claim("evt_123").await?);
+/// assert!(!store.claim("evt_123").await?);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MemoryDedupeStore {
+    ttl: Duration,
+    seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl MemoryDedupeStore {
+    /// Create a store that remembers event IDs for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn claim_sync(&self, event_id: &str) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, at| now.duration_since(*at) < self.ttl);
+
+        if seen.contains_key(event_id) {
+            return false;
+        }
+        seen.insert(event_id.to_string(), now);
+        true
+    }
+}
+
+impl DedupeStore for MemoryDedupeStore {
+    fn claim<'a>(&'a self, event_id: &'a str) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(async move { Ok(self.claim_sync(event_id)) })
+    }
+
+    fn release<'a>(&'a self, event_id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.seen
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(event_id);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteDedupeStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use futures_util::future::BoxFuture;
+    use rusqlite::{params, Connection};
+    use std::path::Path;
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::DedupeStore;
+    use crate::error::{Result, VeilMailError};
+
+    /// A [`DedupeStore`] backed by a SQLite table, shared by every process
+    /// using the same database file.
+    ///
+    /// Requires the `sqlite` feature. Queries are small indexed lookups and
+    /// run directly on the calling task.
+    pub struct SqliteDedupeStore {
+        conn: Mutex<Connection>,
+        ttl: Duration,
+    }
+
+    impl SqliteDedupeStore {
+        /// Open (or create) a database file and remember event IDs for `ttl`.
+        pub fn open(path: impl AsRef<Path>, ttl: Duration) -> Result<Self> {
+            let conn = Connection::open(path).map_err(sqlite_error)?;
+            Self::from_connection(conn, ttl)
+        }
+
+        /// Use an existing connection, creating the table if needed.
+        pub fn from_connection(conn: Connection, ttl: Duration) -> Result<Self> {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS veilmail_webhook_events (
+                    event_id TEXT PRIMARY KEY,
+                    seen_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS veilmail_webhook_events_seen_at
+                    ON veilmail_webhook_events (seen_at);",
+            )
+            .map_err(sqlite_error)?;
+
+            Ok(Self {
+                conn: Mutex::new(conn),
+                ttl,
+            })
+        }
+
+        fn claim_sync(&self, event_id: &str) -> Result<bool> {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64;
+            let cutoff = now - self.ttl.as_secs() as i64;
+
+            let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+            conn.execute(
+                "DELETE FROM veilmail_webhook_events WHERE seen_at <= ?1",
+                params![cutoff],
+            )
+            .map_err(sqlite_error)?;
+            let inserted = conn
+                .execute(
+                    "INSERT OR IGNORE INTO veilmail_webhook_events (event_id, seen_at) VALUES (?1, ?2)",
+                    params![event_id, now],
+                )
+                .map_err(sqlite_error)?;
+
+            Ok(inserted == 1)
+        }
+
+        fn release_sync(&self, event_id: &str) -> Result<()> {
+            let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+            conn.execute(
+                "DELETE FROM veilmail_webhook_events WHERE event_id = ?1",
+                params![event_id],
+            )
+            .map_err(sqlite_error)?;
+            Ok(())
+        }
+    }
+
+    impl DedupeStore for SqliteDedupeStore {
+        fn claim<'a>(&'a self, event_id: &'a str) -> BoxFuture<'a, Result<bool>> {
+            Box::pin(async move { self.claim_sync(event_id) })
+        }
+
+        fn release<'a>(&'a self, event_id: &'a str) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move { self.release_sync(event_id) })
+        }
+    }
+
+    fn sqlite_error(e: rusqlite::Error) -> VeilMailError {
+        VeilMailError::Other(format!("SQLite dedupe store: {}", e))
+    }
+}
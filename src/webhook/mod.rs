@@ -0,0 +1,182 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use sha2::Sha256;
+use std::fmt;
+
+mod dedupe;
+mod router;
+mod testing;
+
+#[cfg(feature = "sqlite")]
+pub use dedupe::SqliteDedupeStore;
+pub use dedupe::{DedupeStore, MemoryDedupeStore};
+pub use router::{Dispatch, HandlerError, HandlerFailure, Headers, WebhookRouter};
+pub use testing::{SignedEvent, TestEventBuilder};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Name of the HTTP header carrying the webhook signature.
+pub const SIGNATURE_HEADER: &str = "x-signature-hash";
+
+/// The kind of event delivered to a webhook endpoint.
+///
+/// Event types this SDK does not know about are preserved in
+/// [`EventType::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EventType {
+    EmailSent,
+    EmailDelivered,
+    EmailBounced,
+    EmailComplained,
+    EmailOpened,
+    EmailClicked,
+    EmailFailed,
+    SubscriberCreated,
+    SubscriberUnsubscribed,
+    Other(String),
+}
+
+impl EventType {
+    /// Every event type known to this SDK.
+    pub const KNOWN: [EventType; 9] = [
+        EventType::EmailSent,
+        EventType::EmailDelivered,
+        EventType::EmailBounced,
+        EventType::EmailComplained,
+        EventType::EmailOpened,
+        EventType::EmailClicked,
+        EventType::EmailFailed,
+        EventType::SubscriberCreated,
+        EventType::SubscriberUnsubscribed,
+    ];
+
+    /// The wire name of the event type, e.g. `email.bounced`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventType::EmailSent => "email.sent",
+            EventType::EmailDelivered => "email.delivered",
+            EventType::EmailBounced => "email.bounced",
+            EventType::EmailComplained => "email.complained",
+            EventType::EmailOpened => "email.opened",
+            EventType::EmailClicked => "email.clicked",
+            EventType::EmailFailed => "email.failed",
+            EventType::SubscriberCreated => "subscriber.created",
+            EventType::SubscriberUnsubscribed => "subscriber.unsubscribed",
+            EventType::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for EventType {
+    fn from(name: &str) -> Self {
+        Self::KNOWN
+            .into_iter()
+            .find(|t| t.as_str() == name)
+            .unwrap_or_else(|| EventType::Other(name.to_string()))
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for EventType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for EventType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(EventType::from(name.as_str()))
+    }
+}
+
+/// A webhook event as delivered in the request body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    /// Unique event ID, stable across redeliveries.
+    #[serde(default)]
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Event-specific payload.
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    /// Parse an event from a raw request body.
+    ///
+    /// This does not check the signature; call [`verify_signature`] first.
+    pub fn parse(body: &str) -> crate::error::Result<Self> {
+        Ok(serde_json::from_str(body)?)
+    }
+}
+
+/// Compute the hex-encoded HMAC-SHA256 signature of a webhook body.
+///
+/// This is the value Veil Mail sends in the [`SIGNATURE_HEADER`] header.
+///
+/// # Example
+///
+/// ```
+/// use veilmail::webhook::{sign, verify_signature};
+///
+/// let body = r#"{"type":"email.delivered"}"#;
+/// let signature = sign(body, "whsec_test");
+/// assert!(verify_signature(body, &signature, "whsec_test"));
+/// ```
+pub fn sign(body: impl AsRef<[u8]>, secret: &str) -> String {
+    // HMAC accepts keys of any length, so this cannot fail.
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body.as_ref());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Verify a webhook signature using constant-time HMAC-SHA256 comparison.
+///
+/// # Arguments
+///
+/// * `body` - The raw request body, exactly as received
+/// * `signature` - The signature from the X-Signature-Hash header
+/// * `secret` - The webhook signing secret
+///
+/// # Returns
+///
+/// `true` if the signature is valid
+///
+/// # Example
+///
+/// ```
+/// use veilmail::webhook::verify_signature;
+///
+/// let body = r#"{"type":"email.delivered"}"#;
+/// let secret = "whsec_test";
+/// // In practice, the signature comes from the X-Signature-Hash header
+/// let valid = verify_signature(body, "some_signature", secret);
+/// ```
+pub fn verify_signature(body: impl AsRef<[u8]>, signature: &str, secret: &str) -> bool {
+    let expected = sign(body, secret);
+    constant_time_eq(expected.as_bytes(), signature.as_bytes())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
@@ -0,0 +1,316 @@
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+
+use super::{verify_signature, DedupeStore, EventType, WebhookEvent, SIGNATURE_HEADER};
+use crate::error::{Result, VeilMailError};
+
+/// Error type returned by webhook handlers.
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+type Handler = Box<
+    dyn Fn(WebhookEvent) -> BoxFuture<'static, std::result::Result<(), HandlerError>> + Send + Sync,
+>;
+
+/// Read access to request headers, independent of the web framework.
+///
+/// Implemented for `reqwest`/`http` header maps (used by Axum, Hyper, and
+/// Warp), `HashMap`s, and slices of name/value pairs.
+pub trait Headers {
+    /// Look up a header by case-insensitive name.
+    fn header(&self, name: &str) -> Option<&str>;
+}
+
+impl Headers for reqwest::header::HeaderMap {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> Headers for [(K, V)] {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.as_ref().eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_ref())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> Headers for Vec<(K, V)> {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.as_slice().header(name)
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>, S> Headers for HashMap<K, V, S> {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.as_ref().eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_ref())
+    }
+}
+
+/// A handler that returned an error or panicked.
+#[derive(Debug)]
+pub struct HandlerFailure {
+    /// Position of the handler among those registered for the event type,
+    /// or `None` for the fallback handler.
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+/// The result of dispatching one webhook delivery.
+#[derive(Debug)]
+pub struct Dispatch {
+    pub event: WebhookEvent,
+    /// Whether the event had already been processed and was skipped.
+    pub duplicate: bool,
+    /// Number of handlers that ran, including failed ones.
+    pub handled: usize,
+    pub failures: Vec<HandlerFailure>,
+}
+
+impl Dispatch {
+    /// Whether every handler completed successfully.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The HTTP status to answer Veil Mail with.
+    ///
+    /// Failed handlers yield `500` so the delivery is retried.
+    pub fn status_code(&self) -> u16 {
+        if self.is_success() {
+            200
+        } else {
+            500
+        }
+    }
+}
+
+/// Routes verified webhook events to async handlers by event type.
+///
+/// Handlers are isolated from each other: an error or panic in one handler
+/// is recorded in the returned [`Dispatch`] and the remaining handlers still
+/// run. Events without a registered handler go to the fallback, if any.
+///
+/// With a [`DedupeStore`] attached via [`WebhookRouter::dedupe`], each event ID
+/// is dispatched at most once; redeliveries are acknowledged without running
+/// any handler.
+///
+/// # Example
+///
+/// ```
+/// use veilmail::webhook::{TestEventBuilder, WebhookRouter};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), veilmail::error::VeilMailError> {
+/// let router = WebhookRouter::new("whsec_test")
+///     .on_bounced(|event| async move {
+///         println!("Bounced: {}", event.data["emailId"]);
+///         Ok(())
+///     })
+///     .fallback(|event| async move {
+///         println!("Unhandled: {}", event.event_type);
+///         Ok(())
+///     });
+///
+/// let delivery = TestEventBuilder::new("email.bounced").sign("whsec_test");
+/// let dispatch = router.handle(&delivery.body, &delivery.headers).await?;
+/// assert_eq!(dispatch.status_code(), 200);
+/// # Ok(())
+/// # }
+/// ```
+pub struct WebhookRouter {
+    secret: String,
+    handlers: HashMap<EventType, Vec<Handler>>,
+    fallback: Option<Handler>,
+    dedupe: Option<Box<dyn DedupeStore>>,
+}
+
+impl WebhookRouter {
+    /// Create a router that verifies deliveries with the given signing secret.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            handlers: HashMap::new(),
+            fallback: None,
+            dedupe: None,
+        }
+    }
+
+    /// Skip events whose ID has already been processed.
+    ///
+    /// If any handler fails, the event ID is released again so that Veil
+    /// Mail's retry is processed.
+    pub fn dedupe(mut self, store: impl DedupeStore + 'static) -> Self {
+        self.dedupe = Some(Box::new(store));
+        self
+    }
+
+    /// Register a handler for an event type.
+    ///
+    /// Several handlers may be registered for the same type; they run in
+    /// registration order.
+    pub fn on<F, Fut>(mut self, event_type: impl Into<EventType>, handler: F) -> Self
+    where
+        F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), HandlerError>> + Send + 'static,
+    {
+        self.handlers
+            .entry(event_type.into())
+            .or_default()
+            .push(boxed(handler));
+        self
+    }
+
+    /// Register a handler for events that have no type-specific handler.
+    pub fn fallback<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), HandlerError>> + Send + 'static,
+    {
+        self.fallback = Some(boxed(handler));
+        self
+    }
+
+    /// Verify, parse, and dispatch a delivery.
+    ///
+    /// Returns [`VeilMailError::InvalidSignature`] when the signature header
+    /// is missing or wrong, and [`VeilMailError::Json`] when the body is not a
+    /// webhook event, and propagates errors from the dedupe store. Handler
+    /// failures do not produce an error; inspect the returned [`Dispatch`]
+    /// instead.
+    pub async fn handle<H: Headers + ?Sized>(&self, body: &str, headers: &H) -> Result<Dispatch> {
+        let signature = headers.header(SIGNATURE_HEADER).unwrap_or("");
+        if !verify_signature(body, signature, &self.secret) {
+            return Err(VeilMailError::InvalidSignature);
+        }
+
+        let event = WebhookEvent::parse(body)?;
+
+        let Some(store) = self.dedupe.as_deref().filter(|_| !event.id.is_empty()) else {
+            return Ok(self.dispatch(event).await);
+        };
+
+        if !store.claim(&event.id).await? {
+            return Ok(Dispatch {
+                event,
+                duplicate: true,
+                handled: 0,
+                failures: Vec::new(),
+            });
+        }
+
+        let dispatch = self.dispatch(event).await;
+        if !dispatch.is_success() {
+            store.release(&dispatch.event.id).await?;
+        }
+        Ok(dispatch)
+    }
+
+    /// Dispatch an already verified event to its handlers.
+    ///
+    /// This bypasses the dedupe store.
+    pub async fn dispatch(&self, event: WebhookEvent) -> Dispatch {
+        let mut failures = Vec::new();
+        let mut handled = 0;
+
+        match self.handlers.get(&event.event_type) {
+            Some(handlers) => {
+                for (index, handler) in handlers.iter().enumerate() {
+                    handled += 1;
+                    if let Err(message) = run(handler, event.clone()).await {
+                        failures.push(HandlerFailure {
+                            index: Some(index),
+                            message,
+                        });
+                    }
+                }
+            }
+            None => {
+                if let Some(handler) = &self.fallback {
+                    handled += 1;
+                    if let Err(message) = run(handler, event.clone()).await {
+                        failures.push(HandlerFailure {
+                            index: None,
+                            message,
+                        });
+                    }
+                }
+            }
+        }
+
+        Dispatch {
+            event,
+            duplicate: false,
+            handled,
+            failures,
+        }
+    }
+}
+
+macro_rules! event_handlers {
+    ($($(#[$doc:meta])* $name:ident => $variant:ident,)*) => {
+        impl WebhookRouter {
+            $(
+                $(#[$doc])*
+                pub fn $name<F, Fut>(self, handler: F) -> Self
+                where
+                    F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+                    Fut: Future<Output = std::result::Result<(), HandlerError>> + Send + 'static,
+                {
+                    self.on(EventType::$variant, handler)
+                }
+            )*
+        }
+    };
+}
+
+event_handlers! {
+    /// Register a handler for `email.sent` events.
+    on_sent => EmailSent,
+    /// Register a handler for `email.delivered` events.
+    on_delivered => EmailDelivered,
+    /// Register a handler for `email.bounced` events.
+    on_bounced => EmailBounced,
+    /// Register a handler for `email.complained` events.
+    on_complained => EmailComplained,
+    /// Register a handler for `email.opened` events.
+    on_opened => EmailOpened,
+    /// Register a handler for `email.clicked` events.
+    on_clicked => EmailClicked,
+    /// Register a handler for `email.failed` events.
+    on_failed => EmailFailed,
+    /// Register a handler for `subscriber.created` events.
+    on_subscriber_created => SubscriberCreated,
+    /// Register a handler for `subscriber.unsubscribed` events.
+    on_subscriber_unsubscribed => SubscriberUnsubscribed,
+}
+
+fn boxed<F, Fut>(handler: F) -> Handler
+where
+    F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = std::result::Result<(), HandlerError>> + Send + 'static,
+{
+    Box::new(move |event| handler(event).boxed())
+}
+
+async fn run(handler: &Handler, event: WebhookEvent) -> std::result::Result<(), String> {
+    let outcome = match std::panic::catch_unwind(AssertUnwindSafe(|| handler(event))) {
+        Ok(future) => AssertUnwindSafe(future).catch_unwind().await,
+        Err(panic) => Err(panic),
+    };
+
+    match outcome {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(panic) => Err(panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .map(|s| format!("handler panicked: {}", s))
+            .unwrap_or_else(|| "handler panicked".to_string())),
+    }
+}
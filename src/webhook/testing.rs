@@ -0,0 +1,153 @@
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{sign, EventType, WebhookEvent, SIGNATURE_HEADER};
+use crate::schedule::rfc3339;
+
+/// Builds realistic, correctly signed webhook deliveries for tests.
+///
+/// Unset fields get sensible defaults: a unique `evt_test_` ID, the current
+/// time, and sample data matching the event type.
+///
+/// # Example
+///
+/// ```
+/// use serde_json::json;
+/// use veilmail::webhook::{verify_signature, EventType, TestEventBuilder};
+///
+/// let delivery = TestEventBuilder::new(EventType::EmailBounced)
+///     .id("evt_123")
+///     .data(json!({ "emailId": "email_123", "bounceType": "hard" }))
+///     .sign("whsec_test");
+///
+/// let signature = delivery.header("x-signature-hash").unwrap();
+/// assert!(verify_signature(&delivery.body, signature, "whsec_test"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestEventBuilder {
+    event_type: EventType,
+    id: Option<String>,
+    created_at: Option<String>,
+    data: Option<Value>,
+}
+
+impl TestEventBuilder {
+    /// Start building an event of the given type.
+    pub fn new(event_type: impl Into<EventType>) -> Self {
+        Self {
+            event_type: event_type.into(),
+            id: None,
+            created_at: None,
+            data: None,
+        }
+    }
+
+    /// Set the event ID.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set the `createdAt` timestamp.
+    pub fn created_at(mut self, created_at: impl Into<String>) -> Self {
+        self.created_at = Some(created_at.into());
+        self
+    }
+
+    /// Replace the sample payload with custom data.
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Build the event without signing it.
+    pub fn build(self) -> WebhookEvent {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        WebhookEvent {
+            id: self
+                .id
+                .unwrap_or_else(|| format!("evt_test_{:x}", now.as_nanos())),
+            created_at: Some(
+                self.created_at
+                    .unwrap_or_else(|| rfc3339(now.as_secs() as i64)),
+            ),
+            data: self.data.unwrap_or_else(|| sample_data(&self.event_type)),
+            event_type: self.event_type,
+        }
+    }
+
+    /// Build the event and sign its serialized body with `secret`.
+    pub fn sign(self, secret: &str) -> SignedEvent {
+        let event = self.build();
+        // Serializing a struct of strings and a `Value` cannot fail.
+        let body = serde_json::to_string(&event).expect("webhook event serializes");
+        let headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            (SIGNATURE_HEADER.to_string(), sign(&body, secret)),
+        ];
+
+        SignedEvent {
+            event,
+            body,
+            headers,
+        }
+    }
+}
+
+/// A webhook delivery as Veil Mail would send it.
+#[derive(Debug, Clone)]
+pub struct SignedEvent {
+    /// The event that was serialized into `body`.
+    pub event: WebhookEvent,
+    /// The raw request body.
+    pub body: String,
+    /// Request headers, including the signature.
+    pub headers: Vec<(String, String)>,
+}
+
+impl SignedEvent {
+    /// Look up a header value by case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn sample_data(event_type: &EventType) -> Value {
+    let mut data = json!({
+        "emailId": "email_test_123",
+        "from": "hello@example.com",
+        "to": ["user@example.com"],
+        "subject": "Test event",
+    });
+
+    let extra = match event_type {
+        EventType::EmailBounced => {
+            json!({ "bounceType": "hard", "reason": "mailbox does not exist" })
+        }
+        EventType::EmailComplained => json!({ "feedbackType": "abuse" }),
+        EventType::EmailClicked => {
+            json!({ "url": "https://example.com/", "userAgent": "veilmail-test" })
+        }
+        EventType::EmailOpened => json!({ "userAgent": "veilmail-test" }),
+        EventType::EmailFailed => json!({ "reason": "rejected by provider" }),
+        EventType::SubscriberCreated | EventType::SubscriberUnsubscribed => {
+            return json!({
+                "subscriberId": "sub_test_123",
+                "audienceId": "aud_test_123",
+                "email": "user@example.com",
+            })
+        }
+        _ => json!({}),
+    };
+
+    if let (Some(base), Value::Object(extra)) = (data.as_object_mut(), extra) {
+        base.extend(extra);
+    }
+    data
+}
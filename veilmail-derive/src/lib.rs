@@ -0,0 +1,183 @@
+//! `#[derive(VeilTemplate)]` for the `veilmail` crate.
+//!
+//! Use it through `veilmail` with the `derive` feature rather than depending
+//! on this crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Token};
+
+/// Implements `veilmail::VeilTemplate` for a struct with named fields.
+///
+/// The template ID comes from `#[veil_template(id = "...")]`. Variable names
+/// are the serialized field names, so `#[serde(rename = "...")]`,
+/// `#[serde(rename_all = "...")]`, and `#[serde(skip)]` are honored.
+#[proc_macro_derive(VeilTemplate, attributes(veil_template))]
+pub fn derive_veil_template(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut template_id: Option<LitStr> = None;
+    let mut rename_all: Option<String> = None;
+
+    for attr in &input.attrs {
+        if attr.path().is_ident("veil_template") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("id") {
+                    template_id = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `id = \"...\"`"))
+                }
+            })?;
+        } else if attr.path().is_ident("serde") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    rename_all = Some(serialized_name(&meta)?);
+                    Ok(())
+                } else {
+                    skip_meta(&meta)
+                }
+            })?;
+        }
+    }
+
+    let template_id = template_id.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "missing `#[veil_template(id = \"...\")]` attribute",
+        )
+    })?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "VeilTemplate requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "VeilTemplate can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut variables = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut rename: Option<String> = None;
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    rename = Some(serialized_name(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("flatten") {
+                    Err(meta.error("VeilTemplate does not support `#[serde(flatten)]`"))
+                } else {
+                    skip_meta(&meta)
+                }
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+        let name = ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name);
+        variables.push(match rename {
+            Some(rename) => rename,
+            None => apply_rename_all(name, rename_all.as_deref(), ident)?,
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::veilmail::VeilTemplate for #ident #ty_generics #where_clause {
+            const TEMPLATE_ID: &'static str = #template_id;
+            const VARIABLES: &'static [&'static str] = &[#(#variables),*];
+        }
+    })
+}
+
+/// Read `name = "..."` or the `serialize` half of `name(serialize = "...")`.
+fn serialized_name(meta: &ParseNestedMeta) -> syn::Result<String> {
+    if meta.input.peek(Token![=]) {
+        let lit: LitStr = meta.value()?.parse()?;
+        return Ok(lit.value());
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("serialize") {
+            let lit: LitStr = inner.value()?.parse()?;
+            name = Some(lit.value());
+            Ok(())
+        } else {
+            skip_meta(&inner)
+        }
+    })?;
+    name.ok_or_else(|| meta.error("expected a `serialize` name"))
+}
+
+/// Consume a serde option this macro does not care about.
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| skip_meta(&inner))?;
+    }
+    Ok(())
+}
+
+fn apply_rename_all(
+    field: &str,
+    rule: Option<&str>,
+    span: &proc_macro2::Ident,
+) -> syn::Result<String> {
+    let words: Vec<&str> = field.split('_').filter(|w| !w.is_empty()).collect();
+    let capitalize = |w: &str| {
+        let mut chars = w.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+
+    Ok(match rule {
+        None | Some("snake_case") => field.to_string(),
+        Some("lowercase") => field.to_lowercase(),
+        Some("UPPERCASE") => field.to_uppercase(),
+        Some("SCREAMING_SNAKE_CASE") => field.to_uppercase(),
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.replace('_', "-").to_uppercase(),
+        Some("PascalCase") => words.iter().map(|w| capitalize(w)).collect(),
+        Some("camelCase") => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.to_string() } else { capitalize(w) })
+            .collect(),
+        Some(other) => {
+            return Err(syn::Error::new_spanned(
+                span,
+                format!("unsupported serde rename_all rule `{}`", other),
+            ))
+        }
+    })
+}
@@ -0,0 +1,122 @@
+//! The derived `VARIABLES` must match the keys serde actually writes.
+
+use serde::Serialize;
+use veilmail::VeilTemplate;
+
+fn serialized_keys<T: Serialize>(value: &T) -> Vec<String> {
+    match serde_json::to_value(value).unwrap() {
+        serde_json::Value::Object(map) => map.keys().cloned().collect(),
+        other => panic!("expected an object, got {}", other),
+    }
+}
+
+fn assert_matches_serde<T: VeilTemplate>(value: &T) {
+    let mut variables: Vec<String> = T::VARIABLES.iter().map(|v| v.to_string()).collect();
+    variables.sort();
+    let mut keys = serialized_keys(value);
+    keys.sort();
+    assert_eq!(variables, keys);
+}
+
+#[derive(Serialize, VeilTemplate)]
+#[veil_template(id = "tmpl_plain")]
+struct Plain {
+    first_name: String,
+    r#type: String,
+}
+
+#[test]
+fn uses_field_names_by_default() {
+    assert_eq!(Plain::TEMPLATE_ID, "tmpl_plain");
+    assert_eq!(Plain::VARIABLES, ["first_name", "type"]);
+    assert_matches_serde(&Plain {
+        first_name: "Ada".into(),
+        r#type: "admin".into(),
+    });
+}
+
+#[derive(Serialize, VeilTemplate)]
+#[veil_template(id = "tmpl_renamed")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Renamed {
+    first_name: String,
+    #[serde(rename = "surname")]
+    last_name: String,
+    #[serde(rename(serialize = "plan_name", deserialize = "plan"))]
+    plan: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order_count: Option<u32>,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    internal_id: u64,
+    #[serde(skip_serializing)]
+    #[allow(dead_code)]
+    secret: String,
+}
+
+#[test]
+fn honors_rename_rename_all_and_skip() {
+    assert_eq!(
+        Renamed::VARIABLES,
+        ["firstName", "surname", "plan_name", "orderCount"]
+    );
+    assert_matches_serde(&Renamed {
+        first_name: "Ada".into(),
+        last_name: "Lovelace".into(),
+        plan: "pro".into(),
+        order_count: Some(2),
+        internal_id: 7,
+        secret: "hidden".into(),
+    });
+}
+
+macro_rules! rename_all_case {
+    ($name:ident, $rule:literal, $expected:expr) => {
+        #[derive(Serialize, VeilTemplate)]
+        #[veil_template(id = "tmpl")]
+        #[serde(rename_all = $rule)]
+        struct $name {
+            first_name: String,
+            last_login_at: String,
+        }
+
+        let value = $name {
+            first_name: String::new(),
+            last_login_at: String::new(),
+        };
+        assert_eq!($name::VARIABLES, $expected, "{}", $rule);
+        assert_matches_serde(&value);
+    };
+}
+
+#[test]
+fn supports_every_rename_all_rule() {
+    rename_all_case!(Snake, "snake_case", ["first_name", "last_login_at"]);
+    rename_all_case!(Lower, "lowercase", ["first_name", "last_login_at"]);
+    rename_all_case!(Upper, "UPPERCASE", ["FIRST_NAME", "LAST_LOGIN_AT"]);
+    rename_all_case!(
+        Screaming,
+        "SCREAMING_SNAKE_CASE",
+        ["FIRST_NAME", "LAST_LOGIN_AT"]
+    );
+    rename_all_case!(Kebab, "kebab-case", ["first-name", "last-login-at"]);
+    rename_all_case!(
+        ScreamingKebab,
+        "SCREAMING-KEBAB-CASE",
+        ["FIRST-NAME", "LAST-LOGIN-AT"]
+    );
+    rename_all_case!(Pascal, "PascalCase", ["FirstName", "LastLoginAt"]);
+    rename_all_case!(Camel, "camelCase", ["firstName", "lastLoginAt"]);
+}
+
+#[derive(Serialize, VeilTemplate)]
+#[veil_template(id = "tmpl_generic")]
+struct Generic<T: Serialize> {
+    item: T,
+}
+
+#[test]
+fn works_with_generics() {
+    assert_eq!(Generic::<u32>::VARIABLES, ["item"]);
+    assert_matches_serde(&Generic { item: 1 });
+}